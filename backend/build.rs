@@ -0,0 +1,24 @@
+use std::process::Command;
+
+// 把构建时的git提交写入 DEMETER_GIT_COMMIT，供 /api/version 展示
+// 没有.git目录的构建环境（如Docker）可以通过同名环境变量传入
+fn main() {
+    println!("cargo:rerun-if-env-changed=DEMETER_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+
+    let commit = std::env::var("DEMETER_GIT_COMMIT").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|commit| commit.trim().to_string())
+    });
+
+    println!(
+        "cargo:rustc-env=DEMETER_GIT_COMMIT={}",
+        commit.unwrap_or_else(|| "unknown".to_string())
+    );
+}
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_todos_created_at;
+DROP INDEX IF EXISTS idx_todos_user_id;
+DROP TABLE IF EXISTS todos;
+DROP TABLE IF EXISTS users;
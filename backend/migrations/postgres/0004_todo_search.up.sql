@@ -0,0 +1,2 @@
+-- PostgreSQL没有FTS5，搜索直接对todos表使用ILIKE；保留这个版本号与SQLite的迁移保持一致
+SELECT 1;
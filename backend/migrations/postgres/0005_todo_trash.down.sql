@@ -0,0 +1,4 @@
+-- 回收站中的任务在回滚后无法区分，直接永久删除
+DELETE FROM todos WHERE deleted_at IS NOT NULL;
+DROP INDEX IF EXISTS idx_todos_deleted_at;
+ALTER TABLE todos DROP COLUMN deleted_at;
@@ -0,0 +1,3 @@
+-- 移到回收站的时间，为空表示任务未删除；回收站中的任务超过保留期限后被永久删除
+ALTER TABLE todos ADD COLUMN deleted_at TIMESTAMPTZ;
+CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos(deleted_at);
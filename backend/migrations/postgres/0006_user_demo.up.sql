@@ -0,0 +1,2 @@
+-- 由演示模式创建的账户，演示模式只会重置带有此标记的账户
+ALTER TABLE users ADD COLUMN demo BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- 用户的界面语言偏好，为空时按请求的Accept-Language决定
+ALTER TABLE users ADD COLUMN locale TEXT;
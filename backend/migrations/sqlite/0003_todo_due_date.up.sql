@@ -0,0 +1,2 @@
+-- 任务的截止日期（不含时间），为空表示没有截止日期
+ALTER TABLE todos ADD COLUMN due_date DATE;
@@ -0,0 +1,26 @@
+-- 任务的全文索引，内容直接取自todos表（外部内容表），由下面的触发器保持同步
+-- trigram分词按3个字符的片段建索引，中文等不用空格分词的文字也能按子串搜索，且不区分大小写
+CREATE VIRTUAL TABLE todos_fts USING fts5(
+    title,
+    description,
+    content = 'todos',
+    content_rowid = 'id',
+    tokenize = 'trigram'
+);
+
+-- 为已有任务建立索引
+INSERT INTO todos_fts (todos_fts) VALUES ('rebuild');
+
+CREATE TRIGGER todos_fts_insert AFTER INSERT ON todos BEGIN
+    INSERT INTO todos_fts (rowid, title, description) VALUES (new.id, new.title, new.description);
+END;
+
+CREATE TRIGGER todos_fts_delete AFTER DELETE ON todos BEGIN
+    INSERT INTO todos_fts (todos_fts, rowid, title, description) VALUES ('delete', old.id, old.title, old.description);
+END;
+
+-- 只有标题或描述变化时才需要更新索引
+CREATE TRIGGER todos_fts_update AFTER UPDATE OF title, description ON todos BEGIN
+    INSERT INTO todos_fts (todos_fts, rowid, title, description) VALUES ('delete', old.id, old.title, old.description);
+    INSERT INTO todos_fts (rowid, title, description) VALUES (new.id, new.title, new.description);
+END;
@@ -0,0 +1,155 @@
+use axum::{
+    middleware,
+    routing::{any, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::{info, warn};
+
+use crate::{
+    backup, bootstrap,
+    config::Config,
+    cors,
+    database::init_database,
+    demo, error, frontend, handlers, health, i18n, logging, metrics, server, trash,
+    models::DemoAccount,
+    storage::{MeteredStorage, Storage},
+};
+
+// 所有handler共享的状态
+#[derive(Clone)]
+pub struct AppState {
+    pub(crate) storage: Arc<dyn Storage>,
+    // 启用的功能，/api/version中展示
+    pub(crate) features: Arc<[&'static str]>,
+    // 演示模式下的演示账户（/api/setup中展示）
+    pub(crate) demo: Option<Arc<DemoAccount>>,
+    // 回收站的保留天数（/api/trash中展示）
+    pub(crate) trash_retention_days: u32,
+}
+
+impl AppState {
+    // storage需要已经完成迁移（见 database::init_database）
+    pub fn new(storage: Arc<dyn Storage>, config: &Config) -> Self {
+        Self {
+            storage,
+            features: health::enabled_features(config).into(),
+            demo: config.demo.enabled.then(|| {
+                Arc::new(DemoAccount {
+                    username: config.demo.username.trim().to_string(),
+                    password: config.demo.password.clone(),
+                })
+            }),
+            trash_retention_days: config.trash.retention_days,
+        }
+    }
+
+    pub fn storage(&self) -> &Arc<dyn Storage> {
+        &self.storage
+    }
+}
+
+// API路由（不含前端页面、CORS和请求日志），可以合并或nest到其他Axum应用中
+pub fn build_router(state: AppState) -> Router {
+    api_routes().merge(health::probe_routes()).with_state(state)
+}
+
+fn api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/login", post(handlers::login_handler))
+        .route("/api/register", post(handlers::register_handler))
+        .route("/api/setup", get(handlers::get_setup_handler).post(handlers::setup_handler))
+        .route("/api/todos", get(handlers::get_todos_handler).post(handlers::create_todo_handler))
+        .route("/api/todos/:id", put(handlers::update_todo_handler).delete(handlers::delete_todo_handler))
+        .route("/api/todos/:id/restore", post(handlers::restore_todo_handler))
+        .route("/api/trash", get(handlers::get_trash_handler))
+        .route("/api/search", get(handlers::search_todos_handler))
+        .route("/api/history", get(handlers::get_history_handler))
+        .route("/api/public-access", get(handlers::get_public_access_handler))
+        .route("/api/user/settings", put(handlers::update_user_settings_handler))
+        // 未定义的API路径返回统一的错误格式，而不是前端页面
+        .route("/api/*path", any(error::api_not_found))
+        // 按Accept-Language和用户偏好选择响应消息的语言
+        .route_layer(middleware::from_fn(i18n::negotiate))
+}
+
+// 按配置启动完整的服务（demeter serve），收到停止信号后返回
+pub async fn serve(config: Config) -> anyhow::Result<()> {
+    // 指标记录器需要在产生任何指标之前安装
+    let metrics_handle = if config.metrics.enabled {
+        Some(metrics::install()?)
+    } else {
+        None
+    };
+
+    // 初始化数据库
+    let mut storage = init_database(&config.database).await?;
+    if metrics_handle.is_some() {
+        storage = Arc::new(MeteredStorage::new(storage));
+    }
+    let state = AppState::new(storage.clone(), &config);
+
+    // 首次启动时创建第一个用户，演示模式下创建演示账户
+    bootstrap::run(storage.users(), &config.bootstrap).await?;
+    if config.demo.enabled {
+        warn!("演示模式已启用: 任何人都可以用 {} 登录，演示数据会定期重置", config.demo.username);
+        demo::reset(storage.as_ref(), &config.demo).await?;
+        demo::spawn_periodic_reset(storage.clone(), config.demo.clone());
+    }
+    if bootstrap::setup_required(storage.users()).await? {
+        warn!("数据库中还没有用户: 请打开前端页面完成首次设置（POST /api/setup），或使用 demeter-admin user create 创建用户");
+    }
+
+    // 定时清理回收站和备份
+    trash::spawn_periodic_purge(storage.clone(), config.trash.clone());
+    backup::spawn_scheduled_backups(storage.clone(), config.backup.clone());
+
+    // 配置CORS
+    let cors = cors::build_layer(&config.server.cors)?;
+
+    // 构建路由
+    let mut app = api_routes();
+
+    // 由后端提供前端页面
+    if let Some(frontend) = frontend::router(&config.frontend)? {
+        app = app.fallback_service(frontend);
+    }
+
+    if metrics_handle.is_some() {
+        app = app.layer(middleware::from_fn(metrics::track_requests));
+    }
+
+    let mut app = app
+        .layer(cors)
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(logging::make_request_span)
+                .on_response(logging::on_response),
+        )
+        // 为每个请求分配x-request-id（客户端已提供时沿用），并在响应中返回
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .merge(health::probe_routes())
+        .with_state(state);
+
+    if let Some(handle) = metrics_handle {
+        let routes = metrics::routes(handle, storage.clone());
+        if config.metrics.admin_port == 0 {
+            app = app.merge(routes);
+        } else {
+            metrics::spawn_admin_server(&config.metrics, routes);
+        }
+    }
+
+    server::run(&config.server, app).await?;
+
+    // 所有请求处理完毕后关闭连接池
+    storage.close().await;
+    info!("服务已停止");
+
+    Ok(())
+}
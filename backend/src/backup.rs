@@ -0,0 +1,185 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteConnectOptions;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{BackupConfig, DatabaseConfig},
+    migrations,
+    storage::{SqliteStorage, Storage},
+};
+
+const BACKUP_PREFIX: &str = "demeter-";
+const BACKUP_SUFFIX: &str = ".db";
+
+// 备份到指定文件
+pub async fn backup_to(storage: &dyn Storage, destination: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = destination.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    storage.backup(destination).await?;
+    info!("数据库已备份到 {}", destination.display());
+    Ok(())
+}
+
+// 在备份目录中生成带时间戳的备份，并按保留数量清理旧备份
+pub async fn backup_with_retention(storage: &dyn Storage, config: &BackupConfig) -> anyhow::Result<PathBuf> {
+    let directory = Path::new(&config.directory);
+    let destination = directory.join(format!(
+        "{}{}{}",
+        BACKUP_PREFIX,
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        BACKUP_SUFFIX
+    ));
+
+    backup_to(storage, &destination).await?;
+    prune_backups(directory, config.retention)?;
+    Ok(destination)
+}
+
+// 删除超出保留数量的旧备份（文件名中的时间戳保证按名称排序即按时间排序）
+fn prune_backups(directory: &Path, retention: usize) -> anyhow::Result<()> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_SUFFIX))
+                .unwrap_or(false)
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(retention);
+    for path in backups.into_iter().take(excess) {
+        fs::remove_file(&path)?;
+        info!("已删除过期备份 {}", path.display());
+    }
+
+    Ok(())
+}
+
+// 启动定时备份任务
+pub fn spawn_scheduled_backups(storage: Arc<dyn Storage>, config: BackupConfig) {
+    if !config.enabled {
+        return;
+    }
+    if config.interval_hours == 0 {
+        warn!("backup.interval_hours 必须大于0，定时备份未启动");
+        return;
+    }
+
+    info!(
+        "定时备份已启用: 每 {} 小时备份到 {}，保留 {} 份",
+        config.interval_hours, config.directory, config.retention
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_hours * 3600));
+        // 第一次tick会立即触发，跳过以避免每次重启都生成备份
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = backup_with_retention(storage.as_ref(), &config).await {
+                error!("定时备份失败: {}", e);
+            }
+        }
+    });
+}
+
+// 从备份文件恢复SQLite数据库；应在服务停止时执行
+pub async fn restore(config: &DatabaseConfig, source: &Path) -> anyhow::Result<PathBuf> {
+    if !config.url.starts_with("sqlite:") {
+        return Err(anyhow::anyhow!("只有SQLite存储后端支持从备份恢复"));
+    }
+    let target = SqliteConnectOptions::from_str(&config.url)?
+        .get_filename()
+        .into_owned();
+    if target.as_os_str().is_empty() || target == Path::new(":memory:") {
+        return Err(anyhow::anyhow!("内存数据库无法恢复"));
+    }
+    if !source.is_file() {
+        return Err(anyhow::anyhow!("备份文件不存在: {}", source.display()));
+    }
+
+    // 先复制到临时文件，校验后再通过重命名替换，避免中途失败破坏现有数据库
+    let staging = with_suffix(&target, ".restore-tmp");
+    fs::copy(source, &staging)?;
+
+    // 校验复制出的文件：完整性和schema版本。FTS5的完整性检查需要写权限，不能以只读方式打开
+    let version = match validate_staging(&staging).await {
+        Ok(version) => version,
+        Err(e) => {
+            remove_database_files(&staging);
+            return Err(e);
+        }
+    };
+    info!("备份文件校验通过，schema版本 {}", version);
+
+    let previous = if target.exists() {
+        let previous = with_suffix(&target, &format!(".before-restore-{}", Utc::now().format("%Y%m%d-%H%M%S")));
+        fs::rename(&target, &previous)?;
+        // 旧数据库的WAL/共享内存文件随原数据库一起移走，不能与恢复后的数据库混用
+        for suffix in ["-wal", "-shm"] {
+            let sidecar = with_suffix(&target, suffix);
+            if sidecar.exists() {
+                fs::rename(&sidecar, with_suffix(&previous, suffix))?;
+            }
+        }
+        Some(previous)
+    } else {
+        None
+    };
+
+    fs::rename(&staging, &target)?;
+
+    if let Some(previous) = &previous {
+        info!("原数据库已保存为 {}", previous.display());
+    }
+    Ok(target)
+}
+
+async fn validate_staging(staging: &Path) -> anyhow::Result<i64> {
+    let backup = SqliteStorage::open_existing(staging).await?;
+    let validation = validate_backup(&backup).await;
+    backup.close().await;
+    validation
+}
+
+fn remove_database_files(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(with_suffix(path, suffix));
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+async fn validate_backup(backup: &SqliteStorage) -> anyhow::Result<i64> {
+    backup.integrity_check().await?;
+
+    let version = migrations::current_version(backup).await?;
+    if version == 0 {
+        return Err(anyhow::anyhow!("备份文件中没有schema版本信息，不是有效的Demeter数据库"));
+    }
+    if version > migrations::latest_version(backup) {
+        return Err(anyhow::anyhow!(
+            "备份文件的schema版本 {} 高于程序支持的版本 {}，请先升级程序",
+            version,
+            migrations::latest_version(backup)
+        ));
+    }
+
+    Ok(version)
+}
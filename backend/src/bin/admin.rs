@@ -0,0 +1,492 @@
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, IsTerminal, Read},
+    path::PathBuf,
+};
+use tracing_subscriber::EnvFilter;
+
+use demeter::{
+    config::{self, Config},
+    database, migrations,
+    i18n::{self, t, t_args, Locale},
+    models::{CreateTodoRequest, User},
+    repository::{ImportedTodo, NewUser},
+    storage::Storage,
+    validation::{parse_date, FieldError, Validate, Validator},
+};
+
+#[derive(Parser)]
+#[command(name = "demeter-admin", version, about = "Demeter 管理工具：用户、任务和数据库维护")]
+struct Cli {
+    /// 配置文件路径，与服务端相同（默认读取当前目录的config.toml）
+    #[arg(long, global = true, env = config::CONFIG_PATH_ENV)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 管理用户
+    User {
+        #[command(subcommand)]
+        action: UserCommand,
+    },
+    /// 查看、导出和导入任务
+    Todo {
+        #[command(subcommand)]
+        action: TodoCommand,
+    },
+    /// 数据库维护
+    Db {
+        #[command(subcommand)]
+        action: DbCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// 列出所有用户
+    List,
+    /// 创建用户
+    Create {
+        username: String,
+        /// 密码；省略时交互输入（非终端时从标准输入读取一行）
+        #[arg(long)]
+        password: Option<String>,
+        /// 任务列表公开访问
+        #[arg(long)]
+        public: bool,
+        /// 只读用户，不能创建、修改或删除任务
+        #[arg(long)]
+        readonly: bool,
+    },
+    /// 删除用户及其所有任务
+    Delete {
+        username: String,
+        /// 确认删除
+        #[arg(long)]
+        yes: bool,
+    },
+    /// 修改密码
+    Passwd {
+        username: String,
+        /// 新密码；省略时交互输入（非终端时从标准输入读取一行）
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// 修改用户设置
+    Set {
+        username: String,
+        /// 任务列表是否公开访问
+        #[arg(long)]
+        public: Option<bool>,
+        /// 是否为只读用户
+        #[arg(long)]
+        readonly: Option<bool>,
+        /// 语言偏好（zh-CN / en），空字符串表示跟随浏览器
+        #[arg(long)]
+        locale: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TodoCommand {
+    /// 列出用户的任务
+    List { username: String },
+    /// 把用户的任务导出为JSON
+    Export {
+        username: String,
+        /// 输出文件，省略时输出到标准输出
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// 从JSON文件导入任务（格式与export相同）
+    Import {
+        username: String,
+        /// 输入文件，"-" 表示标准输入
+        input: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// 应用所有未执行的迁移
+    Migrate,
+    /// 查看迁移状态
+    Status,
+    /// 回收空闲空间并更新统计信息
+    Vacuum,
+    /// 检查数据完整性和schema版本
+    Check,
+}
+
+// 导入导出的任务格式，不包含ID，可以导入到其他用户或实例
+#[derive(Debug, Serialize, Deserialize)]
+struct TodoRecord {
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    emoji: String,
+    #[serde(default)]
+    completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due_date: Option<NaiveDate>,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+
+    // 日志只输出警告和错误到标准错误，不干扰导出到标准输出的数据
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
+        .init();
+    config.warn_ignored_env_vars();
+
+    let default_locale = config.i18n.default_locale()?;
+    i18n::set_default(Locale::from_env().unwrap_or(default_locale));
+
+    let storage = database::connect_database(&config.database).await?;
+    let result = run(storage.as_ref(), cli.command).await;
+    storage.close().await;
+    result
+}
+
+async fn run(storage: &dyn Storage, command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::User { action } => {
+            ensure_schema(storage).await?;
+            run_user(storage, action).await
+        }
+        Command::Todo { action } => {
+            ensure_schema(storage).await?;
+            run_todo(storage, action).await
+        }
+        Command::Db { action } => run_db(storage, action).await,
+    }
+}
+
+async fn run_user(storage: &dyn Storage, action: UserCommand) -> anyhow::Result<()> {
+    match action {
+        UserCommand::List => {
+            let users = storage.users().list().await?;
+            if users.is_empty() {
+                println!("{}", t("admin.no_users"));
+                return Ok(());
+            }
+
+            println!(
+                "{:>4}  {:<24} {:<8} {:<8} {:<8} {:>6}  {}",
+                "ID",
+                t("admin.col_username"),
+                t("admin.col_public"),
+                t("admin.col_readonly"),
+                t("admin.col_locale"),
+                t("admin.col_todos"),
+                t("admin.col_created_at"),
+            );
+            let counts = storage.todos().count_by_user().await?;
+            for user in users {
+                let todos = counts.get(&user.id).copied().unwrap_or(0);
+                println!(
+                    "{:>4}  {:<24} {:<8} {:<8} {:<8} {:>6}  {}",
+                    user.id,
+                    user.username,
+                    yes_no(user.public_access),
+                    yes_no(user.readonly),
+                    user.locale.as_deref().unwrap_or("-"),
+                    todos,
+                    user.created_at.format("%Y-%m-%d %H:%M:%S"),
+                );
+            }
+        }
+        UserCommand::Create { username, password, public, readonly } => {
+            let username = username.trim().to_string();
+            let mut validator = Validator::new();
+            validator.username("username", &username);
+            validator.finish().map_err(field_errors)?;
+
+            if storage.users().find_by_username(&username).await?.is_some() {
+                return Err(anyhow::anyhow!(t_args("admin.username_taken", &[("username", &username)])));
+            }
+
+            let password = read_valid_password(password)?;
+            let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST).context("密码哈希失败")?;
+
+            storage
+                .users()
+                .insert(NewUser {
+                    username: &username,
+                    password_hash: &password_hash,
+                    public_access: public,
+                    readonly,
+                    demo: false,
+                })
+                .await?;
+
+            println!("{}", t_args("admin.user_created", &[("username", &username)]));
+            println!("{}", t(if public { "admin.public" } else { "admin.private" }));
+            println!("{}", t(if readonly { "admin.readonly" } else { "admin.writable" }));
+        }
+        UserCommand::Delete { username, yes } => {
+            let user = find_user(storage, &username).await?;
+            let count = storage.todos().list_for_user(user.id).await?.len();
+            if !yes {
+                return Err(anyhow::anyhow!(t_args(
+                    "admin.delete_confirm",
+                    &[("username", &user.username), ("count", &count)],
+                )));
+            }
+
+            storage.users().delete(user.id).await?;
+            println!("{}", t_args("admin.user_deleted", &[("username", &user.username), ("count", &count)]));
+        }
+        UserCommand::Passwd { username, password } => {
+            let user = find_user(storage, &username).await?;
+            let password = read_valid_password(password)?;
+            let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST).context("密码哈希失败")?;
+            storage.users().set_password_hash(user.id, &password_hash).await?;
+            println!("{}", t_args("admin.password_changed", &[("username", &user.username)]));
+        }
+        UserCommand::Set { username, public, readonly, locale } => {
+            if public.is_none() && readonly.is_none() && locale.is_none() {
+                return Err(anyhow::anyhow!(t("admin.nothing_to_set")));
+            }
+            let user = find_user(storage, &username).await?;
+
+            // 语言在修改任何字段前先校验
+            let locale = match locale.as_deref().map(str::trim) {
+                Some("") => Some(None),
+                Some(tag) => {
+                    let mut validator = Validator::new();
+                    let parsed = validator.locale("locale", tag);
+                    validator.finish().map_err(field_errors)?;
+                    parsed.map(|locale| Some(locale.tag()))
+                }
+                None => None,
+            };
+
+            if let Some(public) = public {
+                storage.users().set_public_access(user.id, public).await?;
+                println!("{}", t(if public { "admin.public" } else { "admin.private" }));
+            }
+            if let Some(readonly) = readonly {
+                storage.users().set_readonly(user.id, readonly).await?;
+                println!("{}", t(if readonly { "admin.readonly" } else { "admin.writable" }));
+            }
+            if let Some(locale) = locale {
+                storage.users().set_locale(user.id, locale).await?;
+                match locale {
+                    Some(locale) => println!("{}", t_args("admin.locale", &[("locale", &locale)])),
+                    None => println!("{}", t("admin.locale_cleared")),
+                }
+            }
+            println!("{}", t_args("admin.user_updated", &[("username", &user.username)]));
+        }
+    }
+    Ok(())
+}
+
+async fn run_todo(storage: &dyn Storage, action: TodoCommand) -> anyhow::Result<()> {
+    match action {
+        TodoCommand::List { username } => {
+            let user = find_user(storage, &username).await?;
+            let todos = storage.todos().list_for_user(user.id).await?;
+            if todos.is_empty() {
+                println!("{}", t_args("admin.no_todos", &[("username", &user.username)]));
+                return Ok(());
+            }
+
+            for todo in todos {
+                println!(
+                    "{:>5}  {}  {} {}  {}",
+                    todo.id,
+                    if todo.completed { "✅" } else { "⬜" },
+                    todo.emoji,
+                    todo.title,
+                    todo.created_at.format("%Y-%m-%d %H:%M"),
+                );
+            }
+        }
+        TodoCommand::Export { username, output } => {
+            let user = find_user(storage, &username).await?;
+            let todos = storage.todos().list_for_user(user.id).await?;
+            let records: Vec<TodoRecord> = todos
+                .into_iter()
+                .map(|todo| TodoRecord {
+                    title: todo.title,
+                    description: todo.description,
+                    emoji: todo.emoji,
+                    completed: todo.completed,
+                    due_date: todo.due_date,
+                    created_at: Some(todo.created_at),
+                    updated_at: Some(todo.updated_at),
+                })
+                .collect();
+            let json = serde_json::to_string_pretty(&records)?;
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, json + "\n").with_context(|| format!("无法写入 {}", path.display()))?;
+                    // 提示输出到标准错误，标准输出只用于数据
+                    eprintln!("{}", t_args(
+                        "admin.todos_exported",
+                        &[("count", &records.len()), ("path", &path.display())],
+                    ));
+                }
+                None => println!("{}", json),
+            }
+        }
+        TodoCommand::Import { username, input } => {
+            let user = find_user(storage, &username).await?;
+            let content = if input.as_os_str() == "-" {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                content
+            } else {
+                fs::read_to_string(&input).with_context(|| format!("无法读取 {}", input.display()))?
+            };
+            let records: Vec<TodoRecord> = serde_json::from_str(&content).context("任务JSON格式错误")?;
+
+            // 全部校验通过后才开始写入，避免导入一半
+            let mut todos = Vec::with_capacity(records.len());
+            for (index, record) in records.into_iter().enumerate() {
+                let mut request = CreateTodoRequest {
+                    title: record.title,
+                    description: record.description,
+                    emoji: record.emoji,
+                    due_date: record.due_date.map(|due_date| due_date.to_string()),
+                };
+                if let Err(errors) = request.validate() {
+                    return Err(anyhow::anyhow!(t_args(
+                        "admin.todo_invalid",
+                        &[("index", &(index + 1)), ("errors", &format_field_errors(&errors))],
+                    )));
+                }
+                todos.push((request, record.completed, record.created_at, record.updated_at));
+            }
+
+            // 在同一个事务中写入，中途失败时不会留下部分任务
+            let now = Utc::now();
+            let todos: Vec<ImportedTodo> = todos
+                .iter()
+                .map(|(request, completed, created_at, updated_at)| {
+                    let created_at = created_at.unwrap_or(now);
+                    ImportedTodo {
+                        user_id: user.id,
+                        title: &request.title,
+                        description: request.description.as_deref(),
+                        emoji: &request.emoji,
+                        due_date: request.due_date.as_deref().and_then(parse_date),
+                        completed: *completed,
+                        created_at,
+                        updated_at: updated_at.unwrap_or(created_at),
+                    }
+                })
+                .collect();
+            let count = storage.todos().import(&todos).await?;
+            println!("{}", t_args(
+                "admin.todos_imported",
+                &[("username", &user.username), ("count", &count)],
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn run_db(storage: &dyn Storage, action: DbCommand) -> anyhow::Result<()> {
+    match action {
+        DbCommand::Migrate => migrations::print_applied(&migrations::migrate_up(storage).await?),
+        DbCommand::Status => migrations::print_status(storage).await?,
+        DbCommand::Vacuum => {
+            storage.vacuum().await?;
+            println!("{}", t("admin.vacuum_done"));
+        }
+        DbCommand::Check => {
+            storage.check().await?;
+            ensure_schema(storage).await?;
+            println!("{}", t_args("admin.check_ok", &[("version", &migrations::current_version(storage).await?)]));
+        }
+    }
+    Ok(())
+}
+
+// 用户和任务命令要求schema与程序版本一致，避免在旧库上报出难懂的SQL错误
+async fn ensure_schema(storage: &dyn Storage) -> anyhow::Result<()> {
+    let current = migrations::current_version(storage).await?;
+    let latest = migrations::latest_version(storage);
+    if current != latest {
+        return Err(anyhow::anyhow!(t_args(
+            "admin.schema_mismatch",
+            &[("current", &current), ("latest", &latest)],
+        )));
+    }
+    Ok(())
+}
+
+async fn find_user(storage: &dyn Storage, username: &str) -> anyhow::Result<User> {
+    storage
+        .users()
+        .find_by_username(username.trim())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!(t_args("admin.user_not_found", &[("username", &username)])))
+}
+
+// 使用与注册接口相同的密码规则
+fn read_valid_password(password: Option<String>) -> anyhow::Result<String> {
+    let password = match password {
+        Some(password) => password,
+        None => read_password()?,
+    };
+
+    let mut validator = Validator::new();
+    validator.password("password", &password);
+    validator.finish().map_err(field_errors)?;
+    Ok(password)
+}
+
+// 终端中不回显地输入两次；管道输入时读取一行，便于脚本使用
+fn read_password() -> anyhow::Result<String> {
+    if !io::stdin().is_terminal() {
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    let password = rpassword::prompt_password(t("admin.password_prompt"))?;
+    let confirm = rpassword::prompt_password(t("admin.password_confirm"))?;
+    if password != confirm {
+        return Err(anyhow::anyhow!(t("admin.password_mismatch")));
+    }
+    Ok(password)
+}
+
+fn yes_no(value: bool) -> String {
+    t(if value { "admin.yes" } else { "admin.no" })
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| t_args("admin.invalid_field", &[("field", &error.field), ("message", &error.message)]))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn field_errors(errors: Vec<FieldError>) -> anyhow::Error {
+    anyhow::anyhow!(format_field_errors(&errors))
+}
@@ -0,0 +1,420 @@
+use chrono::{Local, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use std::io::{self, IsTerminal, Write};
+
+use demeter::{
+    client::{
+        self, heat_level, parse_due_date, ApiError, Client, Credentials, Heatmap, HistorySummary, HEAT_COLORS,
+    },
+    error::ErrorCode,
+    i18n::{self, t, t_args, Locale},
+    models::{
+        CreateTodoRequest, HistoryDay, LoginRequest, SearchQuery, TodoListQuery, TodoResponse, UpdateTodoRequest,
+    },
+    search,
+};
+
+#[derive(Parser)]
+#[command(name = "demeter-cli", version, about = "Demeter 命令行客户端：在终端中管理任务")]
+struct Cli {
+    /// 服务器地址，默认使用登录时的地址
+    #[arg(long, global = true, env = client::SERVER_ENV)]
+    server: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 登录并保存token
+    Login {
+        /// 用户名；省略时交互输入
+        username: Option<String>,
+        /// 密码；省略时交互输入（非终端时从标准输入读取一行）
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// 删除保存的登录信息
+    Logout,
+    /// 创建任务
+    Add {
+        title: String,
+        #[arg(long, short, default_value = "📝")]
+        emoji: String,
+        #[arg(long, short)]
+        description: Option<String>,
+        /// 截止日期：YYYY-MM-DD、today、tomorrow、+3d、+2w、friday
+        #[arg(long)]
+        due: Option<String>,
+    },
+    /// 列出任务
+    Ls {
+        /// 按标题和描述筛选（不区分大小写）
+        query: Option<String>,
+        /// 只显示未完成的任务
+        #[arg(long, conflicts_with = "done")]
+        open: bool,
+        /// 只显示已完成的任务
+        #[arg(long)]
+        done: bool,
+        /// 只显示已过期且未完成的任务
+        #[arg(long)]
+        overdue: bool,
+        /// 只显示在该日期（含）之前到期的任务
+        #[arg(long)]
+        due: Option<String>,
+        /// 只显示指定emoji的任务
+        #[arg(long, short)]
+        emoji: Option<String>,
+    },
+    /// 全文搜索任务，按相关度排序
+    Search {
+        /// 搜索词，多个词需要同时匹配
+        #[arg(required = true)]
+        terms: Vec<String>,
+        /// 最多显示的结果数
+        #[arg(long, short = 'n', default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..=100))]
+        limit: u32,
+    },
+    /// 把任务标记为已完成
+    Done {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+        /// 改回未完成
+        #[arg(long)]
+        undo: bool,
+    },
+    /// 修改任务，未指定的字段保持不变
+    Edit {
+        id: i64,
+        #[arg(long, short)]
+        title: Option<String>,
+        #[arg(long, short)]
+        emoji: Option<String>,
+        /// 描述，空字符串表示清除
+        #[arg(long, short)]
+        description: Option<String>,
+        /// 截止日期，空字符串表示清除
+        #[arg(long)]
+        due: Option<String>,
+    },
+    /// 把任务移到回收站
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// 列出回收站中的任务
+    Trash,
+    /// 从回收站恢复任务
+    Restore {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// 以热力图显示最近的任务历史
+    History {
+        /// 显示的周数
+        #[arg(long, short, default_value_t = 26, value_parser = clap::value_parser!(u32).range(1..=53))]
+        weeks: u32,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    i18n::set_default(Locale::from_env().unwrap_or_default());
+
+    let result = run(cli).await;
+    // 未登录或token失效时提示重新登录
+    if let Err(error) = &result {
+        if let Some(ApiError { code: Some(ErrorCode::Unauthorized), .. }) = error.downcast_ref() {
+            return Err(anyhow::anyhow!("{}\n{}", error, t("client.login_hint")));
+        }
+    }
+    result
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    let credentials = Credentials::load()?;
+
+    if let Command::Login { username, password } = cli.command {
+        let server = cli
+            .server
+            .or_else(|| credentials.map(|credentials| credentials.server))
+            .unwrap_or_else(|| client::DEFAULT_SERVER.to_string());
+        return login(&server, username, password).await;
+    }
+    if let Command::Logout = cli.command {
+        match Credentials::remove()? {
+            true => println!("{}", t("client.logged_out")),
+            false => println!("{}", t("client.not_logged_in")),
+        }
+        return Ok(());
+    }
+
+    let client = Client::from_credentials(cli.server, credentials.as_ref())?;
+    // 截止日期是用户所在时区的日期，"today"、"+3d"和过期判断都按本地日期计算
+    let today = Local::now().date_naive();
+
+    match cli.command {
+        Command::Login { .. } | Command::Logout => unreachable!(),
+        Command::Add { title, emoji, description, due } => {
+            let due_date = due.map(|due| resolve_due(&due, today)).transpose()?;
+            let todo = client
+                .create_todo(&CreateTodoRequest {
+                    title,
+                    description,
+                    emoji,
+                    due_date: due_date.map(|date| date.to_string()),
+                })
+                .await?;
+            println!("{}", t_args("client.todo_created", &[("todo", &format_todo(&todo, today))]));
+        }
+        Command::Ls { query, open, done, overdue, due, emoji } => {
+            let due_before = due.map(|due| resolve_due(&due, today)).transpose()?;
+            // 完成状态、emoji和关键词由服务器筛选，截止日期在本地筛选
+            let query = TodoListQuery {
+                completed: if open { Some(false) } else if done { Some(true) } else { None },
+                emoji,
+                q: query,
+                ..Default::default()
+            };
+            let todos: Vec<TodoResponse> = client
+                .list_todos(&query)
+                .await?
+                .into_iter()
+                .filter(|todo| !overdue || is_overdue(todo, today))
+                .filter(|todo| due_before.is_none() || todo.due_date.is_some_and(|date| Some(date) <= due_before))
+                .collect();
+
+            if todos.is_empty() {
+                println!("{}", t("client.no_todos"));
+                return Ok(());
+            }
+            for todo in &todos {
+                println!("{}", format_todo(todo, today));
+            }
+            println!("{}", t_args("client.todo_count", &[
+                ("count", &todos.len()),
+                ("open", &todos.iter().filter(|todo| !todo.completed).count()),
+            ]));
+        }
+        Command::Search { terms, limit } => {
+            let page = client
+                .search(&SearchQuery {
+                    q: terms.join(" "),
+                    limit: Some(limit),
+                })
+                .await?;
+
+            if page.data.is_empty() {
+                println!("{}", t("client.no_todos"));
+                return Ok(());
+            }
+            let color = use_color();
+            for hit in &page.data {
+                let todo = TodoResponse {
+                    title: render_highlight(&hit.title, color),
+                    ..hit.todo.clone()
+                };
+                println!("{}", format_todo(&todo, today));
+                if let Some(snippet) = &hit.snippet {
+                    println!("        {}", render_highlight(snippet, color));
+                }
+            }
+            println!("{}", t_args("client.search_count", &[
+                ("shown", &page.data.len()),
+                ("total", &page.meta.total),
+            ]));
+        }
+        Command::Done { ids, undo } => {
+            for id in ids {
+                let update = UpdateTodoRequest {
+                    completed: Some(!undo),
+                    ..Default::default()
+                };
+                let todo = client.update_todo(id, &update).await?;
+                println!("{}", format_todo(&todo, today));
+            }
+        }
+        Command::Edit { id, title, emoji, description, due } => {
+            if title.is_none() && emoji.is_none() && description.is_none() && due.is_none() {
+                return Err(anyhow::anyhow!(t("client.nothing_to_edit")));
+            }
+            let due_date = match due.as_deref().map(str::trim) {
+                Some("") => Some(String::new()),
+                Some(due) => Some(resolve_due(due, today)?.to_string()),
+                None => None,
+            };
+            let update = UpdateTodoRequest {
+                title,
+                description,
+                emoji,
+                completed: None,
+                due_date,
+            };
+            let todo = client.update_todo(id, &update).await?;
+            println!("{}", t_args("client.todo_updated", &[("todo", &format_todo(&todo, today))]));
+        }
+        Command::Rm { ids } => {
+            for id in ids {
+                client.delete_todo(id).await?;
+                println!("{}", t_args("client.todo_deleted", &[("id", &id)]));
+            }
+        }
+        Command::Trash => {
+            let trash = client.trash().await?;
+            if trash.data.is_empty() {
+                println!("{}", t("client.trash_empty"));
+                return Ok(());
+            }
+            for todo in &trash.data {
+                let mut line = format_todo(todo, today);
+                if let Some(deleted_at) = todo.deleted_at {
+                    let time = deleted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+                    line.push_str(&format!("  {}", t_args("client.trash_deleted_at", &[("time", &time)])));
+                }
+                println!("{}", line);
+            }
+            if trash.retention_days > 0 {
+                println!("{}", t_args("client.trash_retention", &[("days", &trash.retention_days)]));
+            }
+        }
+        Command::Restore { ids } => {
+            for id in ids {
+                let todo = client.restore_todo(id).await?;
+                println!("{}", t_args("client.todo_restored", &[("todo", &format_todo(&todo, today))]));
+            }
+        }
+        Command::History { weeks } => {
+            let history = client.history().await?;
+            // 服务端按UTC日期统计历史
+            print_heatmap(&history, Utc::now().date_naive(), weeks);
+        }
+    }
+    Ok(())
+}
+
+async fn login(server: &str, username: Option<String>, password: Option<String>) -> anyhow::Result<()> {
+    let username = match username {
+        Some(username) => username,
+        None => prompt(&t("client.username_prompt"))?,
+    };
+    let password = match password {
+        Some(password) => password,
+        None if io::stdin().is_terminal() => rpassword::prompt_password(t("client.password_prompt"))?,
+        None => prompt("")?,
+    };
+
+    let client = Client::new(server, None)?;
+    let response = client.login(&LoginRequest { username, password }).await?;
+    let credentials = Credentials {
+        server: client.server().to_string(),
+        username: response.user.username,
+        token: response.token,
+    };
+    let path = credentials.save()?;
+    println!("{}", t_args("client.logged_in", &[
+        ("username", &credentials.username),
+        ("server", &credentials.server),
+    ]));
+    println!("{}", t_args("client.credentials_saved", &[("path", &path.display())]));
+    Ok(())
+}
+
+// 读取一行输入，非终端时不显示提示
+fn prompt(message: &str) -> anyhow::Result<String> {
+    if io::stdin().is_terminal() {
+        print!("{}", message);
+        io::stdout().flush()?;
+    }
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn resolve_due(input: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+    parse_due_date(input, today).ok_or_else(|| anyhow::anyhow!(t_args("client.invalid_due", &[("due", &input)])))
+}
+
+fn is_overdue(todo: &TodoResponse, today: NaiveDate) -> bool {
+    !todo.completed && todo.due_date.is_some_and(|date| date < today)
+}
+
+// 输出到终端且未设置NO_COLOR时使用ANSI颜色
+fn use_color() -> bool {
+    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+fn format_todo(todo: &TodoResponse, today: NaiveDate) -> String {
+    let mut line = format!(
+        "{:>5}  {}  {} {}",
+        todo.id,
+        if todo.completed { "✅" } else { "⬜" },
+        todo.emoji,
+        todo.title,
+    );
+    if let Some(due_date) = todo.due_date {
+        let due = t_args("client.due", &[("date", &due_date)]);
+        if !is_overdue(todo, today) {
+            line.push_str(&format!("  {}", due));
+        } else if use_color() {
+            line.push_str(&format!("  \x1b[31m{} {}\x1b[0m", due, t("client.overdue")));
+        } else {
+            line.push_str(&format!("  {} {}", due, t("client.overdue")));
+        }
+    }
+    line
+}
+
+// 把搜索结果中的<mark>高亮转换为终端的加粗黄色，并还原HTML转义
+fn render_highlight(text: &str, color: bool) -> String {
+    let (start, end) = if color { ("\x1b[1;33m", "\x1b[0m") } else { ("", "") };
+    text.replace(search::HIGHLIGHT_START, start)
+        .replace(search::HIGHLIGHT_END, end)
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// 无颜色时按等级使用不同深浅的字符
+const HEAT_CHARS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+fn heat_cell(level: usize, color: bool) -> String {
+    if color {
+        let (r, g, b) = HEAT_COLORS[level];
+        format!("\x1b[38;2;{};{};{}m■\x1b[0m ", r, g, b)
+    } else {
+        format!("{} ", HEAT_CHARS[level])
+    }
+}
+
+// 行为星期一到星期日，列为周，最后一列是本周
+fn print_heatmap(history: &[HistoryDay], today: NaiveDate, weeks: u32) {
+    const LABEL_WIDTH: usize = 4;
+    let heatmap = Heatmap::new(history, today, weeks);
+    let color = use_color();
+
+    println!("{}", heatmap.month_header(LABEL_WIDTH));
+    for weekday in 0..7 {
+        let mut line = Heatmap::weekday_label(weekday, LABEL_WIDTH);
+        for count in heatmap.weeks.iter().map_while(|week| week[weekday]) {
+            line.push_str(&heat_cell(heat_level(count), color));
+        }
+        println!("{}", line.trim_end());
+    }
+
+    let legend: String = (0..HEAT_COLORS.len()).map(|level| heat_cell(level, color)).collect();
+    println!();
+    println!("{} {} {}", t("client.less"), legend.trim_end(), t("client.more"));
+
+    let summary = HistorySummary::new(history, today);
+    println!("{}", t_args("client.history_summary", &[
+        ("total", &summary.total),
+        ("completed", &summary.completed),
+        ("rate", &summary.completion_rate()),
+        ("streak", &summary.streak),
+    ]));
+}
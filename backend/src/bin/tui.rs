@@ -0,0 +1,650 @@
+use chrono::{Local, NaiveDate, Utc};
+use clap::Parser;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use demeter::{
+    client::{self, heat_level, parse_due_date, Client, Credentials, Heatmap, HistorySummary, HEAT_COLORS},
+    i18n::{self, t, t_args, Locale},
+    models::{CreateTodoRequest, HistoryDay, TodoListQuery, TodoResponse, UpdateTodoRequest},
+};
+
+#[derive(Parser)]
+#[command(name = "demeter-tui", version, about = "Demeter 终端界面：键盘操作的任务列表和历史热力图")]
+struct Cli {
+    /// 服务器地址，默认使用 demeter-cli login 保存的地址
+    #[arg(long, env = client::SERVER_ENV)]
+    server: Option<String>,
+}
+
+// 表单字段的顺序
+const FIELD_TITLE: usize = 0;
+const FIELD_EMOJI: usize = 1;
+const FIELD_DESCRIPTION: usize = 2;
+const FIELD_LABELS: [&str; 4] = ["tui.field_title", "tui.field_emoji", "tui.field_description", "tui.field_due"];
+
+// emoji选择器中的常用emoji，每行 PICKER_COLUMNS 个；不使用带变体选择符的emoji（如✈️），部分终端中宽度不一致
+const EMOJIS: &[&str] = &[
+    "📝", "✅", "🔥", "⭐", "💡", "📌", "🎯", "🚀",
+    "📚", "💻", "🦀", "🐛", "🔧", "📦", "📊", "🔍",
+    "🏃", "💪", "🧘", "🍎", "🥬", "🛒", "🍳", "☕",
+    "🏠", "🧹", "🧺", "💰", "📞", "📧", "📅", "⏰",
+    "🎉", "🎁", "🎵", "🎮", "🛫", "🚗", "🌱", "💖",
+];
+const PICKER_COLUMNS: usize = 8;
+
+const HEATMAP_LABEL_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    All,
+    Open,
+    Done,
+}
+
+impl Filter {
+    fn next(self) -> Self {
+        match self {
+            Filter::All => Filter::Open,
+            Filter::Open => Filter::Done,
+            Filter::Done => Filter::All,
+        }
+    }
+
+    fn label(self) -> String {
+        t(match self {
+            Filter::All => "tui.filter_all",
+            Filter::Open => "tui.filter_open",
+            Filter::Done => "tui.filter_done",
+        })
+    }
+
+    fn matches(self, todo: &TodoResponse) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Open => !todo.completed,
+            Filter::Done => todo.completed,
+        }
+    }
+}
+
+// 新建或编辑任务的表单，id为None时是新建
+#[derive(Clone)]
+struct Form {
+    id: Option<i64>,
+    fields: [String; 4],
+    focus: usize,
+}
+
+impl Form {
+    fn new() -> Self {
+        Self {
+            id: None,
+            fields: [String::new(), "📝".to_string(), String::new(), String::new()],
+            focus: FIELD_TITLE,
+        }
+    }
+
+    fn edit(todo: &TodoResponse) -> Self {
+        Self {
+            id: Some(todo.id),
+            fields: [
+                todo.title.clone(),
+                todo.emoji.clone(),
+                todo.description.clone().unwrap_or_default(),
+                todo.due_date.map(|date| date.to_string()).unwrap_or_default(),
+            ],
+            focus: FIELD_TITLE,
+        }
+    }
+}
+
+enum Mode {
+    Normal,
+    Form(Form),
+    // 选择器关闭后回到表单
+    EmojiPicker { form: Form, selected: usize },
+    ConfirmDelete(TodoResponse),
+}
+
+struct Status {
+    message: String,
+    error: bool,
+}
+
+struct App {
+    client: Client,
+    username: Option<String>,
+    todos: Vec<TodoResponse>,
+    history: Vec<HistoryDay>,
+    filter: Filter,
+    list: ListState,
+    mode: Mode,
+    status: Option<Status>,
+    quit: bool,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    i18n::set_default(Locale::from_env().unwrap_or_default());
+
+    let credentials = Credentials::load()?;
+    let client = Client::from_credentials(cli.server, credentials.as_ref())?;
+    let username = credentials
+        .filter(|_| client.is_logged_in())
+        .map(|credentials| credentials.username);
+
+    // 进入全屏之前先加载一次，服务器不可用时直接在终端里报错
+    let mut app = App::new(client, username);
+    app.reload().await?;
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(client: Client, username: Option<String>) -> Self {
+        Self {
+            client,
+            username,
+            todos: Vec::new(),
+            history: Vec::new(),
+            filter: Filter::All,
+            list: ListState::default(),
+            mode: Mode::Normal,
+            status: None,
+            quit: false,
+        }
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn reload(&mut self) -> anyhow::Result<()> {
+        self.todos = self.client.list_todos(&TodoListQuery::default()).await?;
+        self.history = self.client.history().await?;
+        self.clamp_selection();
+        Ok(())
+    }
+
+    // 历史热力图在任务变化后重新获取
+    async fn reload_history(&mut self) -> anyhow::Result<()> {
+        self.history = self.client.history().await?;
+        Ok(())
+    }
+
+    fn visible(&self) -> Vec<&TodoResponse> {
+        self.todos.iter().filter(|todo| self.filter.matches(todo)).collect()
+    }
+
+    fn selected(&self) -> Option<&TodoResponse> {
+        self.list.selected().and_then(|index| self.visible().get(index).copied())
+    }
+
+    fn clamp_selection(&mut self) {
+        let count = self.visible().len();
+        match self.list.selected() {
+            _ if count == 0 => self.list.select(None),
+            Some(index) if index >= count => self.list.select(Some(count - 1)),
+            None => self.list.select(Some(0)),
+            Some(_) => {}
+        }
+    }
+
+    fn set_status(&mut self, message: String) {
+        self.status = Some(Status { message, error: false });
+    }
+
+    fn report(&mut self, result: anyhow::Result<()>) {
+        if let Err(error) = result {
+            // 字段校验错误等多行错误在状态栏中合并为一行
+            let message = format!("{:#}", error).replace('\n', "; ");
+            self.status = Some(Status { message, error: true });
+        }
+    }
+
+    // 截止日期是用户所在时区的日期，输入和过期判断都按本地日期计算
+    fn today() -> NaiveDate {
+        Local::now().date_naive()
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => {
+                self.status = None;
+                let result = self.handle_normal_key(key).await;
+                self.report(result);
+            }
+            Mode::Form(form) => {
+                let result = self.handle_form_key(form, key).await;
+                self.report(result);
+            }
+            Mode::EmojiPicker { form, selected } => self.handle_picker_key(form, selected, key),
+            Mode::ConfirmDelete(todo) => {
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    let result = self.delete(todo).await;
+                    self.report(result);
+                }
+            }
+        }
+    }
+
+    async fn handle_normal_key(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.list.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.list.select_last(),
+            KeyCode::Char('f') | KeyCode::Tab => {
+                self.filter = self.filter.next();
+                self.list.select(Some(0));
+            }
+            KeyCode::Char('r') => {
+                self.reload().await?;
+                self.set_status(t("tui.reloaded"));
+            }
+            KeyCode::Char('a') => self.mode = Mode::Form(Form::new()),
+            KeyCode::Char('e') => {
+                if let Some(todo) = self.selected() {
+                    self.mode = Mode::Form(Form::edit(todo));
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(todo) = self.selected() {
+                    self.mode = Mode::ConfirmDelete(todo.clone());
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(todo) = self.selected() {
+                    let (id, completed) = (todo.id, !todo.completed);
+                    self.toggle(id, completed).await?;
+                }
+            }
+            _ => {}
+        }
+        // select_next等方法不检查列表长度
+        self.clamp_selection();
+        Ok(())
+    }
+
+    async fn handle_form_key(&mut self, mut form: Form, key: KeyEvent) -> anyhow::Result<()> {
+        match key.code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % form.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + form.fields.len() - 1) % form.fields.len(),
+            KeyCode::Enter if form.focus == FIELD_EMOJI => {
+                let selected = EMOJIS.iter().position(|emoji| *emoji == form.fields[FIELD_EMOJI]).unwrap_or(0);
+                self.mode = Mode::EmojiPicker { form, selected };
+                return Ok(());
+            }
+            KeyCode::Enter => {
+                // 保存失败时保留表单，方便修改后重试
+                let retry = form.clone();
+                if let Err(error) = self.save(form).await {
+                    self.mode = Mode::Form(retry);
+                    return Err(error);
+                }
+                return Ok(());
+            }
+            KeyCode::Backspace => {
+                form.fields[form.focus].pop();
+            }
+            KeyCode::Char(c) => form.fields[form.focus].push(c),
+            _ => {}
+        }
+        self.mode = Mode::Form(form);
+        Ok(())
+    }
+
+    fn handle_picker_key(&mut self, mut form: Form, mut selected: usize, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Form(form);
+                return;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                form.fields[FIELD_EMOJI] = EMOJIS[selected].to_string();
+                form.focus = FIELD_DESCRIPTION;
+                self.mode = Mode::Form(form);
+                return;
+            }
+            KeyCode::Left | KeyCode::Char('h') => selected = selected.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => selected = (selected + 1).min(EMOJIS.len() - 1),
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(PICKER_COLUMNS),
+            KeyCode::Down | KeyCode::Char('j') => selected = (selected + PICKER_COLUMNS).min(EMOJIS.len() - 1),
+            _ => {}
+        }
+        self.mode = Mode::EmojiPicker { form, selected };
+    }
+
+    async fn toggle(&mut self, id: i64, completed: bool) -> anyhow::Result<()> {
+        let update = UpdateTodoRequest {
+            completed: Some(completed),
+            ..Default::default()
+        };
+        let todo = self.client.update_todo(id, &update).await?;
+        self.replace(todo);
+        self.reload_history().await
+    }
+
+    async fn save(&mut self, form: Form) -> anyhow::Result<()> {
+        let [title, emoji, description, due] = form.fields;
+        let due = due.trim();
+        let due_date = match due {
+            "" => None,
+            due => Some(
+                parse_due_date(due, Self::today())
+                    .ok_or_else(|| anyhow::anyhow!(t_args("client.invalid_due", &[("due", &due)])))?
+                    .to_string(),
+            ),
+        };
+
+        match form.id {
+            None => {
+                let request = CreateTodoRequest {
+                    title,
+                    description: Some(description),
+                    emoji,
+                    due_date,
+                };
+                let todo = self.client.create_todo(&request).await?;
+                self.set_status(t_args("tui.created", &[("title", &todo.title)]));
+                self.todos.insert(0, todo);
+                self.filter = Filter::All;
+                self.list.select(Some(0));
+            }
+            Some(id) => {
+                // 空的描述和截止日期表示清除
+                let request = UpdateTodoRequest {
+                    title: Some(title),
+                    description: Some(description),
+                    emoji: Some(emoji),
+                    completed: None,
+                    due_date: Some(due_date.unwrap_or_default()),
+                };
+                let todo = self.client.update_todo(id, &request).await?;
+                self.set_status(t_args("tui.updated", &[("title", &todo.title)]));
+                self.replace(todo);
+            }
+        }
+        self.clamp_selection();
+        self.reload_history().await
+    }
+
+    async fn delete(&mut self, todo: TodoResponse) -> anyhow::Result<()> {
+        self.client.delete_todo(todo.id).await?;
+        self.todos.retain(|existing| existing.id != todo.id);
+        self.clamp_selection();
+        self.set_status(t_args("tui.deleted", &[("title", &todo.title)]));
+        self.reload_history().await
+    }
+
+    fn replace(&mut self, todo: TodoResponse) {
+        if let Some(existing) = self.todos.iter_mut().find(|existing| existing.id == todo.id) {
+            *existing = todo;
+        }
+        self.clamp_selection();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, bottom, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(5),
+            Constraint::Length(11),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [detail, heatmap] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(bottom);
+
+        self.draw_header(frame, header);
+        self.draw_list(frame, body);
+        self.draw_detail(frame, detail);
+        self.draw_heatmap(frame, heatmap);
+        self.draw_footer(frame, footer);
+
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Form(form) => draw_form(frame, form),
+            Mode::EmojiPicker { selected, .. } => draw_picker(frame, *selected),
+            Mode::ConfirmDelete(todo) => draw_confirm(frame, todo),
+        }
+    }
+
+    fn draw_header(&self, frame: &mut Frame, area: Rect) {
+        let user = match &self.username {
+            Some(username) => format!("{}@{}", username, self.client.server()),
+            None => t_args("tui.public_view", &[("server", &self.client.server())]),
+        };
+        let line = Line::from(vec![
+            " Demeter ".bold().reversed(),
+            format!(" {}  ", user).into(),
+            format!("[{}]", self.filter.label()).cyan(),
+        ]);
+        frame.render_widget(line, area);
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let today = Self::today();
+        let visible = self.visible();
+        let block = Block::bordered().title(t_args("tui.todos", &[("count", &visible.len())]));
+
+        if visible.is_empty() {
+            frame.render_widget(Paragraph::new(t("tui.no_todos")).dim().block(block), area);
+            return;
+        }
+
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|todo| {
+                let mut spans = vec![
+                    Span::raw(if todo.completed { "✅ " } else { "⬜ " }),
+                    Span::raw(format!("{} ", todo.emoji)),
+                ];
+                let title = Span::raw(todo.title.clone());
+                spans.push(if todo.completed { title.dim().crossed_out() } else { title });
+                if let Some(due_date) = todo.due_date {
+                    let due = Span::raw(format!("  {}", t_args("client.due", &[("date", &due_date)])));
+                    spans.push(if !todo.completed && due_date < today { due.red() } else { due.dim() });
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("› ");
+        frame.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(t("tui.detail"));
+        let Some(todo) = self.selected() else {
+            frame.render_widget(block, area);
+            return;
+        };
+
+        let mut lines = vec![Line::from(format!("{} {}", todo.emoji, todo.title)).bold()];
+        if let Some(description) = &todo.description {
+            lines.push(Line::from(description.clone()));
+        }
+        lines.push(Line::default());
+        if let Some(due_date) = todo.due_date {
+            lines.push(Line::from(t_args("client.due", &[("date", &due_date)])));
+        }
+        let time_format = "%Y-%m-%d %H:%M";
+        lines.push(
+            Line::from(t_args("tui.created_at", &[
+                ("time", &todo.created_at.with_timezone(&Local).format(time_format)),
+            ]))
+            .dim(),
+        );
+        lines.push(
+            Line::from(t_args("tui.updated_at", &[
+                ("time", &todo.updated_at.with_timezone(&Local).format(time_format)),
+            ]))
+            .dim(),
+        );
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+    }
+
+    // 周数随面板宽度变化，每个格子占两列
+    fn draw_heatmap(&self, frame: &mut Frame, area: Rect) {
+        // 服务端按UTC日期统计历史
+        let today = Utc::now().date_naive();
+        let block = Block::bordered().title(t("tui.history"));
+        let columns = (area.width as usize).saturating_sub(2 + HEATMAP_LABEL_WIDTH) / 2;
+        let heatmap = Heatmap::new(&self.history, today, columns.clamp(1, 53) as u32);
+
+        let cell = |level: usize| {
+            let (r, g, b) = HEAT_COLORS[level];
+            Span::styled("■ ", Style::default().fg(Color::Rgb(r, g, b)))
+        };
+
+        let mut lines = vec![Line::from(heatmap.month_header(HEATMAP_LABEL_WIDTH)).dim()];
+        for weekday in 0..7 {
+            let mut spans = vec![Span::raw(Heatmap::weekday_label(weekday, HEATMAP_LABEL_WIDTH)).dim()];
+            spans.extend(
+                heatmap
+                    .weeks
+                    .iter()
+                    .map_while(|week| week[weekday])
+                    .map(|count| cell(heat_level(count))),
+            );
+            lines.push(Line::from(spans));
+        }
+
+        let mut legend = vec![Span::raw(format!("{} ", t("client.less"))).dim()];
+        legend.extend((0..HEAT_COLORS.len()).map(cell));
+        legend.push(Span::raw(t("client.more")).dim());
+        lines.push(Line::from(legend));
+
+        let summary = HistorySummary::new(&self.history, today);
+        let block = block.title_bottom(format!(" {} ", t_args("client.history_summary", &[
+            ("total", &summary.total),
+            ("completed", &summary.completed),
+            ("rate", &summary.completion_rate()),
+            ("streak", &summary.streak),
+        ])));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let line = match &self.status {
+            Some(Status { message, error: true }) => Line::from(message.clone()).red(),
+            Some(Status { message, error: false }) => Line::from(message.clone()).green(),
+            None => Line::from(t(match self.mode {
+                Mode::Normal => "tui.help_normal",
+                Mode::Form(_) => "tui.help_form",
+                Mode::EmojiPicker { .. } => "tui.help_picker",
+                Mode::ConfirmDelete(_) => "tui.help_confirm",
+            }))
+            .dim(),
+        };
+        frame.render_widget(line, area);
+    }
+}
+
+// 屏幕中央的弹出框
+fn popup_area(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    area
+}
+
+fn draw_form(frame: &mut Frame, form: &Form) {
+    let area = popup_area(frame.area(), 64, FIELD_LABELS.len() as u16 + 2);
+    let title = t(if form.id.is_some() { "tui.edit_todo" } else { "tui.new_todo" });
+    let block = Block::bordered().title(title).title_bottom(t("tui.due_hint"));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let label_width = FIELD_LABELS
+        .iter()
+        .map(|label| client::display_width(&t(label)))
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let rows = Layout::vertical([Constraint::Length(1); 4]).split(inner);
+    for (index, (row, label)) in rows.iter().zip(FIELD_LABELS).enumerate() {
+        let label = t(label);
+        let padding = " ".repeat(label_width - client::display_width(&label));
+        let style = if index == form.focus {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::DIM)
+        };
+        let line = Line::from(vec![
+            Span::styled(format!("{}{}", label, padding), style),
+            Span::raw(form.fields[index].clone()),
+        ]);
+        frame.render_widget(line, *row);
+
+        if index == form.focus {
+            let x = row.x + (label_width + client::display_width(&form.fields[index])) as u16;
+            frame.set_cursor_position(Position::new(x.min(row.right().saturating_sub(1)), row.y));
+        }
+    }
+}
+
+fn draw_picker(frame: &mut Frame, selected: usize) {
+    let rows = EMOJIS.len().div_ceil(PICKER_COLUMNS) as u16;
+    let area = popup_area(frame.area(), (PICKER_COLUMNS * 4 + 2) as u16, rows + 2);
+    frame.render_widget(Clear, area);
+
+    let lines: Vec<Line> = EMOJIS
+        .chunks(PICKER_COLUMNS)
+        .enumerate()
+        .map(|(row, emojis)| {
+            Line::from(
+                emojis
+                    .iter()
+                    .enumerate()
+                    .map(|(column, emoji)| {
+                        let span = Span::raw(format!(" {} ", emoji));
+                        if row * PICKER_COLUMNS + column == selected {
+                            span.reversed()
+                        } else {
+                            span
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(t("tui.emoji_picker"))), area);
+}
+
+fn draw_confirm(frame: &mut Frame, todo: &TodoResponse) {
+    let message = t_args("tui.confirm_delete", &[("title", &format!("{} {}", todo.emoji, todo.title))]);
+    let width = (client::display_width(&message) + 4).clamp(30, 72) as u16;
+    let area = popup_area(frame.area(), width, 3);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(message).block(Block::bordered().red()), area);
+}
@@ -0,0 +1,84 @@
+use anyhow::Context;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::{
+    config::BootstrapConfig,
+    models::{RegisterRequest, User},
+    repository::{NewUser, UserRepository},
+    validation::{FieldError, Validate},
+};
+
+// 同一时刻只处理一个首次设置请求，避免并发请求各自创建出"第一个"用户
+static SETUP_LOCK: Mutex<()> = Mutex::const_new(());
+
+// 数据库中还没有任何用户时需要首次设置
+pub async fn setup_required(users: &dyn UserRepository) -> anyhow::Result<bool> {
+    Ok(users.first().await?.is_none())
+}
+
+// 注册前检查首次设置已经完成：等待进行中的首次设置结束，避免注册抢先创建出第一个用户
+pub async fn setup_completed(users: &dyn UserRepository) -> anyhow::Result<bool> {
+    let _guard = SETUP_LOCK.lock().await;
+    Ok(!setup_required(users).await?)
+}
+
+// 创建第一个用户；已有用户时返回None。用户名和密码需要已经通过校验
+pub async fn setup(
+    users: &dyn UserRepository,
+    username: &str,
+    password: &str,
+    public_access: bool,
+) -> anyhow::Result<Option<User>> {
+    let _guard = SETUP_LOCK.lock().await;
+    if !setup_required(users).await? {
+        return Ok(None);
+    }
+
+    let password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST).context("密码哈希错误")?;
+    let user = users
+        .insert(NewUser {
+            username,
+            password_hash: &password_hash,
+            public_access,
+            readonly: false,
+            demo: false,
+        })
+        .await
+        .context("创建用户失败")?;
+    Ok(Some(user))
+}
+
+// 启动时按[bootstrap]配置创建第一个用户，已有用户时忽略配置
+pub async fn run(users: &dyn UserRepository, config: &BootstrapConfig) -> anyhow::Result<()> {
+    if !config.is_configured() {
+        return Ok(());
+    }
+    let (username, password) = validate_account("bootstrap", &config.username, &config.password)?;
+
+    match setup(users, &username, &password, config.public_access).await? {
+        Some(user) => info!("已按[bootstrap]配置创建第一个用户: {}", user.username),
+        None => info!("数据库中已有用户，忽略[bootstrap]配置"),
+    }
+    Ok(())
+}
+
+// 按注册规则校验配置文件中的账户，返回规范化后的用户名和密码
+pub(crate) fn validate_account(section: &str, username: &str, password: &str) -> anyhow::Result<(String, String)> {
+    let mut request = RegisterRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    };
+    request.validate().map_err(|errors| {
+        anyhow::anyhow!("[{}] 配置的账户不合法: {}", section, format_field_errors(&errors))
+    })?;
+    Ok((request.username, request.password))
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
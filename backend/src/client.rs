@@ -0,0 +1,426 @@
+use anyhow::Context;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, io::Write, path::PathBuf};
+
+use crate::{
+    error::ErrorCode,
+    i18n,
+    models::{
+        ApiResponse, CreateTodoRequest, HistoryDay, LoginRequest, LoginResponse, Page, SearchHit, SearchQuery,
+        TodoListQuery, TodoResponse, TrashResponse, UpdateTodoRequest,
+    },
+    validation::{parse_date, FieldError, PAGE_MAX_LIMIT},
+};
+
+// 服务器地址，优先于登录时保存的地址
+pub const SERVER_ENV: &str = "DEMETER_SERVER";
+// 登录信息文件路径，默认为 ~/.config/demeter/cli.toml
+pub const CREDENTIALS_PATH_ENV: &str = "DEMETER_CLI_CONFIG";
+pub const DEFAULT_SERVER: &str = "http://localhost:9999";
+
+// 登录后保存的服务器地址和token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub server: String,
+    pub username: String,
+    pub token: String,
+}
+
+impl Credentials {
+    pub fn path() -> anyhow::Result<PathBuf> {
+        if let Some(path) = std::env::var_os(CREDENTIALS_PATH_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+        let dir = dirs::config_dir().context("无法确定用户配置目录")?;
+        Ok(dir.join("demeter").join("cli.toml"))
+    }
+
+    // 尚未登录时返回None
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).with_context(|| format!("无法读取 {}", path.display()))?;
+        let credentials = toml::from_str(&content).with_context(|| format!("登录信息文件格式错误: {}", path.display()))?;
+        Ok(Some(credentials))
+    }
+
+    // 文件中包含token，只允许当前用户读写
+    pub fn save(&self) -> anyhow::Result<PathBuf> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("无法创建目录 {}", dir.display()))?;
+        }
+        let content = toml::to_string(self)?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // 新文件创建时就是0600，写入token之前其他用户无法读取
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path).with_context(|| format!("无法写入 {}", path.display()))?;
+        // 已有的文件保留原来的权限，同样先收紧再写入
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("无法写入 {}", path.display()))?;
+        Ok(path)
+    }
+
+    // 删除登录信息，文件不存在时返回false
+    pub fn remove() -> anyhow::Result<bool> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path).with_context(|| format!("无法删除 {}", path.display()))?;
+        Ok(true)
+    }
+}
+
+// 服务器返回的错误，可以从anyhow::Error中downcast出来按错误码处理
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub errors: Vec<FieldError>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        for error in &self.errors {
+            write!(f, "\n  {}: {}", error.field, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+// Demeter HTTP API的客户端，错误提示使用与命令行相同的语言
+pub struct Client {
+    http: reqwest::Client,
+    server: String,
+    token: Option<String>,
+}
+
+impl Client {
+    pub fn new(server: &str, token: Option<String>) -> anyhow::Result<Self> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            header::HeaderValue::from_static(i18n::current().tag()),
+        );
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("demeter-cli/", env!("CARGO_PKG_VERSION")))
+            .default_headers(headers)
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self {
+            http,
+            server: server.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    // 优先使用指定的服务器，其次是登录时的服务器；只有访问登录时的服务器才带上token，
+    // 未登录时可以查看公开的任务
+    pub fn from_credentials(server: Option<String>, credentials: Option<&Credentials>) -> anyhow::Result<Self> {
+        match (server, credentials) {
+            (Some(server), Some(credentials)) if same_server(&server, &credentials.server) => {
+                Self::new(&server, Some(credentials.token.clone()))
+            }
+            (Some(server), _) => Self::new(&server, None),
+            (None, Some(credentials)) => Self::new(&credentials.server, Some(credentials.token.clone())),
+            (None, None) => Self::new(DEFAULT_SERVER, None),
+        }
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.token.is_some()
+    }
+
+    pub async fn login(&self, request: &LoginRequest) -> anyhow::Result<LoginResponse> {
+        self.send(self.request(Method::POST, "/api/login").json(request)).await
+    }
+
+    pub async fn list_todos_page(&self, query: &TodoListQuery) -> anyhow::Result<Page<TodoResponse>> {
+        self.send(self.request(Method::GET, "/api/todos").query(query)).await
+    }
+
+    pub async fn search(&self, query: &SearchQuery) -> anyhow::Result<Page<SearchHit>> {
+        self.send(self.request(Method::GET, "/api/search").query(query)).await
+    }
+
+    // 按筛选条件逐页获取全部任务
+    pub async fn list_todos(&self, query: &TodoListQuery) -> anyhow::Result<Vec<TodoResponse>> {
+        let mut query = TodoListQuery {
+            limit: Some(PAGE_MAX_LIMIT),
+            cursor: None,
+            ..query.clone()
+        };
+        let mut todos = Vec::new();
+        loop {
+            let page = self.list_todos_page(&query).await?;
+            todos.extend(page.data);
+            match page.meta.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => return Ok(todos),
+            }
+        }
+    }
+
+    pub async fn create_todo(&self, request: &CreateTodoRequest) -> anyhow::Result<TodoResponse> {
+        self.send(self.request(Method::POST, "/api/todos").json(request)).await
+    }
+
+    pub async fn update_todo(&self, id: i64, request: &UpdateTodoRequest) -> anyhow::Result<TodoResponse> {
+        self.send(self.request(Method::PUT, &format!("/api/todos/{}", id)).json(request)).await
+    }
+
+    // 移到回收站
+    pub async fn delete_todo(&self, id: i64) -> anyhow::Result<()> {
+        self.execute(self.request(Method::DELETE, &format!("/api/todos/{}", id))).await?;
+        Ok(())
+    }
+
+    pub async fn restore_todo(&self, id: i64) -> anyhow::Result<TodoResponse> {
+        self.send(self.request(Method::POST, &format!("/api/todos/{}/restore", id))).await
+    }
+
+    pub async fn trash(&self) -> anyhow::Result<TrashResponse> {
+        self.send(self.request(Method::GET, "/api/trash")).await
+    }
+
+    // 最近一年按日期汇总的任务
+    pub async fn history(&self) -> anyhow::Result<Vec<HistoryDay>> {
+        self.send(self.request(Method::GET, "/api/history")).await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.http.request(method, format!("{}{}", self.server, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> anyhow::Result<T> {
+        let response = self.execute(request).await?;
+        response.json().await.context("服务器响应格式错误")
+    }
+
+    // 非2xx响应统一转换为ApiError
+    async fn execute(&self, request: RequestBuilder) -> anyhow::Result<Response> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("无法连接服务器 {}", self.server))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let error = match serde_json::from_str::<ApiResponse<serde_json::Value>>(&body) {
+            Ok(body) => ApiError {
+                status,
+                code: body.code,
+                message: body.message,
+                errors: body.errors.unwrap_or_default(),
+            },
+            // 不是Demeter服务器的响应（如反向代理的错误页）
+            Err(_) => ApiError {
+                status,
+                code: None,
+                message: format!("{} {}", status, body.trim()).trim().to_string(),
+                errors: Vec::new(),
+            },
+        };
+        Err(error.into())
+    }
+}
+
+fn same_server(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+// 解析截止日期：YYYY-MM-DD、today/tomorrow/yesterday、+N（天）、+Nw（周）、星期名（下一个该星期几）
+pub fn parse_due_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    if let Some(date) = parse_date(&input) {
+        return Some(date);
+    }
+
+    match input.as_str() {
+        "today" | "今天" => return Some(today),
+        "tomorrow" | "明天" => return Some(today + Duration::days(1)),
+        "yesterday" | "昨天" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let (number, unit) = match offset.strip_suffix('w') {
+            Some(number) => (number, 7),
+            None => (offset.strip_suffix('d').unwrap_or(offset), 1),
+        };
+        let number: i64 = number.parse().ok()?;
+        return today.checked_add_signed(Duration::try_days(number.checked_mul(unit)?)?);
+    }
+
+    let weekday: Weekday = input.parse().ok()?;
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    Some(today + Duration::days(if days == 0 { 7 } else { days.into() }))
+}
+
+// 热力图颜色等级（0-4），与前端历史页面的阈值一致
+pub fn heat_level(count: i64) -> usize {
+    match count {
+        ..=0 => 0,
+        1..=2 => 1,
+        3..=4 => 2,
+        5..=6 => 3,
+        _ => 4,
+    }
+}
+
+// 各等级的颜色（RGB），与前端历史页面一致
+pub const HEAT_COLORS: [(u8, u8, u8); 5] = [
+    (0xeb, 0xed, 0xf0),
+    (0x9b, 0xe9, 0xa8),
+    (0x40, 0xc4, 0x63),
+    (0x30, 0xa1, 0x4e),
+    (0x21, 0x6e, 0x39),
+];
+
+// 热力图网格，每列是一周（星期一到星期日），最后一列是本周；今天之后的日期为None
+pub struct Heatmap {
+    pub start: NaiveDate,
+    pub weeks: Vec<[Option<i64>; 7]>,
+}
+
+impl Heatmap {
+    pub fn new(history: &[HistoryDay], today: NaiveDate, weeks: u32) -> Self {
+        let counts: HashMap<NaiveDate, i64> = history
+            .iter()
+            .filter_map(|day| Some((parse_date(&day.date)?, day.count)))
+            .collect();
+        let start = today
+            - Duration::days(today.weekday().num_days_from_monday().into())
+            - Duration::weeks(i64::from(weeks.max(1)) - 1);
+
+        let weeks = (0..weeks.max(1))
+            .map(|week| {
+                let monday = start + Duration::weeks(week.into());
+                std::array::from_fn(|weekday| {
+                    let date = monday + Duration::days(weekday as i64);
+                    (date <= today).then(|| counts.get(&date).copied().unwrap_or(0))
+                })
+            })
+            .collect();
+        Self { start, weeks }
+    }
+
+    // 第几列所在周的星期一
+    pub fn monday(&self, week: usize) -> NaiveDate {
+        self.start + Duration::weeks(week as i64)
+    }
+
+    // 月份标签的位置：(列号, 月份1-12)，标在每个月第一次出现的那一列
+    pub fn month_starts(&self) -> Vec<(usize, u32)> {
+        let mut starts: Vec<(usize, u32)> = Vec::new();
+        for week in 0..self.weeks.len() {
+            let month = self.monday(week).month();
+            if starts.last().is_none_or(|&(_, previous)| previous != month) {
+                starts.push((week, month));
+            }
+        }
+        starts
+    }
+
+    // 月份标签行，标签放在该月第一周的上方，空间不够时跳过；每个格子占两列
+    pub fn month_header(&self, label_width: usize) -> String {
+        let months = i18n::t("client.month_names");
+        let months: Vec<&str> = months.split(',').collect();
+
+        let mut header = " ".repeat(label_width);
+        let mut width = label_width;
+        for (week, month) in self.month_starts() {
+            let position = label_width + week * 2;
+            let label = months.get(month as usize - 1).copied().unwrap_or_default();
+            if width <= position {
+                header.push_str(&" ".repeat(position - width));
+                header.push_str(label);
+                width = position + display_width(label);
+            }
+        }
+        header.trim_end().to_string()
+    }
+
+    // 行首的星期标签，只标注周一、周三、周五，补齐到指定宽度
+    pub fn weekday_label(weekday: usize, label_width: usize) -> String {
+        let weekdays = i18n::t("client.weekday_names");
+        let label = match weekday {
+            0 | 2 | 4 => weekdays.split(',').nth(weekday).unwrap_or_default(),
+            _ => "",
+        };
+        format!("{}{}", label, " ".repeat(label_width.saturating_sub(display_width(label))))
+    }
+}
+
+// 终端显示宽度的近似值，中文等全角字符占两列
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+// 历史统计（总数、完成数、截至今天的连续活跃天数）
+#[derive(Debug, Default)]
+pub struct HistorySummary {
+    pub total: i64,
+    pub completed: i64,
+    pub streak: i64,
+}
+
+impl HistorySummary {
+    pub fn new(history: &[HistoryDay], today: NaiveDate) -> Self {
+        let mut summary = Self {
+            total: history.iter().map(|day| day.count).sum(),
+            completed: history.iter().map(|day| day.completed_count).sum(),
+            streak: 0,
+        };
+
+        let active = |date: NaiveDate| {
+            history
+                .iter()
+                .any(|day| day.count > 0 && parse_date(&day.date) == Some(date))
+        };
+        // 今天还没有任务时从昨天开始计算
+        let mut date = if active(today) { today } else { today - Duration::days(1) };
+        while active(date) {
+            summary.streak += 1;
+            date -= Duration::days(1);
+        }
+        summary
+    }
+
+    pub fn completion_rate(&self) -> i64 {
+        if self.total == 0 {
+            0
+        } else {
+            (self.completed * 100 + self.total / 2) / self.total
+        }
+    }
+}
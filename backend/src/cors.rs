@@ -0,0 +1,118 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::{str::FromStr, time::Duration};
+use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
+
+use crate::config::CorsConfig;
+
+// 根据配置构建CORS中间件，配置错误在启动时报出
+pub fn build_layer(config: &CorsConfig) -> anyhow::Result<CorsLayer> {
+    // 不声明任何允许的来源，浏览器会拒绝所有跨域请求
+    if config.same_origin_only {
+        return Ok(CorsLayer::new());
+    }
+
+    let any_origin = config.allowed_origins.iter().any(|origin| origin == "*");
+    let any_header = config.allowed_headers.iter().any(|header| header == "*");
+    // CORS规范不允许携带凭据时使用通配符
+    if config.allow_credentials && (any_origin || any_header) {
+        return Err(anyhow::anyhow!("CORS配置错误: allow_credentials 不能与 \"*\" 来源或请求头同时使用"));
+    }
+
+    let origin = if any_origin {
+        AllowOrigin::any()
+    } else {
+        let patterns = config
+            .allowed_origins
+            .iter()
+            .map(|origin| OriginPattern::parse(origin))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+            origin
+                .to_str()
+                .map(|origin| patterns.iter().any(|pattern| pattern.matches(origin)))
+                .unwrap_or(false)
+        })
+    };
+
+    let methods = config
+        .allowed_methods
+        .iter()
+        .map(|method| {
+            Method::from_str(&method.to_uppercase())
+                .map_err(|_| anyhow::anyhow!("CORS配置错误: 无效的请求方法 {}", method))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let headers = if any_header {
+        AllowHeaders::any()
+    } else {
+        AllowHeaders::list(parse_header_names(&config.allowed_headers)?)
+    };
+
+    let mut layer = CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(parse_header_names(&config.expose_headers)?)
+        .allow_credentials(config.allow_credentials);
+    if config.max_age_secs > 0 {
+        layer = layer.max_age(Duration::from_secs(config.max_age_secs));
+    }
+
+    Ok(layer)
+}
+
+fn parse_header_names(names: &[String]) -> anyhow::Result<Vec<HeaderName>> {
+    names
+        .iter()
+        .map(|name| {
+            HeaderName::from_str(name).map_err(|_| anyhow::anyhow!("CORS配置错误: 无效的请求头 {}", name))
+        })
+        .collect()
+}
+
+// 允许的来源：完整匹配，或 "scheme://*.domain[:port]" 匹配该域名的任意子域名
+enum OriginPattern {
+    Exact(String),
+    Subdomain { scheme: String, suffix: String },
+}
+
+impl OriginPattern {
+    fn parse(pattern: &str) -> anyhow::Result<Self> {
+        let pattern = pattern.trim_end_matches('/');
+        let invalid = || anyhow::anyhow!("CORS配置错误: 无效的来源 {}，格式应为 scheme://host[:port]", pattern);
+
+        let (scheme, host) = pattern.split_once("://").ok_or_else(invalid)?;
+        if scheme.is_empty() || host.is_empty() || host.contains('/') {
+            return Err(invalid());
+        }
+
+        match host.strip_prefix('*') {
+            // 只允许 "*." 开头，避免 "*example.com" 误匹配 "evilexample.com"
+            Some(suffix) if suffix.starts_with('.') && !suffix.contains('*') => Ok(Self::Subdomain {
+                scheme: format!("{}://", scheme.to_lowercase()),
+                suffix: suffix.to_lowercase(),
+            }),
+            Some(_) => Err(invalid()),
+            None if host.contains('*') => Err(invalid()),
+            None => Ok(Self::Exact(pattern.to_lowercase())),
+        }
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        let origin = origin.to_lowercase();
+        match self {
+            Self::Exact(expected) => origin == *expected,
+            Self::Subdomain { scheme, suffix } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|host| host.strip_suffix(suffix.as_str()))
+                .map(|subdomain| {
+                    !subdomain.is_empty()
+                        && subdomain
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+                })
+                .unwrap_or(false),
+        }
+    }
+}
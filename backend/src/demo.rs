@@ -0,0 +1,129 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::{
+    bootstrap::validate_account,
+    config::DemoConfig,
+    models::User,
+    repository::{NewTodo, NewUser},
+    storage::Storage,
+};
+
+struct SampleTodo {
+    emoji: &'static str,
+    title: &'static str,
+    description: Option<&'static str>,
+    // 创建于几天前，用于填充历史热力图
+    days_ago: i64,
+    completed: bool,
+    // 截止日期相对今天的天数，负数表示已过期
+    due_in_days: Option<i64>,
+}
+
+const SAMPLE_TODOS: &[SampleTodo] = &[
+    SampleTodo { emoji: "📚", title: "读完《人类简史》第三章", description: None, days_ago: 20, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🏃", title: "晨跑5公里", description: Some("沿河边的路线"), days_ago: 18, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🛒", title: "周末采购", description: Some("牛奶、鸡蛋、面包、水果"), days_ago: 14, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🏃", title: "晨跑5公里", description: None, days_ago: 11, completed: true, due_in_days: None },
+    SampleTodo { emoji: "💼", title: "准备季度汇报", description: Some("整理数据并做好幻灯片"), days_ago: 9, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🎂", title: "给妈妈订生日蛋糕", description: None, days_ago: 6, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🦷", title: "预约牙医", description: None, days_ago: 5, completed: false, due_in_days: Some(-1) },
+    SampleTodo { emoji: "🏃", title: "晨跑5公里", description: None, days_ago: 3, completed: true, due_in_days: None },
+    SampleTodo { emoji: "🌱", title: "给阳台的植物浇水", description: None, days_ago: 2, completed: false, due_in_days: Some(0) },
+    SampleTodo { emoji: "✈️", title: "订国庆假期的机票", description: Some("比较一下早班和晚班的价格"), days_ago: 1, completed: false, due_in_days: Some(7) },
+    SampleTodo { emoji: "💡", title: "试试Demeter的历史热力图", description: Some("点击右上角的历史记录查看"), days_ago: 0, completed: false, due_in_days: None },
+];
+
+// 创建或重置演示账户：恢复默认设置和密码，并用示例任务替换已有任务。
+// 同名的普通用户不是演示账户，拒绝重置，避免演示模式接管并清空真实用户的数据
+pub async fn reset(storage: &dyn Storage, config: &DemoConfig) -> anyhow::Result<User> {
+    let (username, password) = validate_account("demo", &config.username, &config.password)?;
+    let users = storage.users();
+    let todos = storage.todos();
+
+    let user = match users.find_by_username(&username).await? {
+        Some(user) if !user.demo => {
+            anyhow::bail!(
+                "用户 {} 不是演示账户，拒绝重置；请在[demo]中配置其他用户名",
+                user.username
+            );
+        }
+        Some(user) => {
+            // 保留原来的密码哈希，已登录的访客不会因为重置而退出
+            if !bcrypt::verify(&password, &user.password_hash).unwrap_or(false) {
+                users.set_password_hash(user.id, &bcrypt::hash(&password, bcrypt::DEFAULT_COST)?).await?;
+            }
+            users.set_public_access(user.id, true).await?;
+            users.set_readonly(user.id, false).await?;
+            users.set_locale(user.id, None).await?;
+            // 回收站中的任务也一并永久删除
+            for todo in todos.list_for_user(user.id).await?.into_iter().chain(todos.list_trash(user.id).await?) {
+                todos.delete(todo.id, user.id).await?;
+            }
+            user
+        }
+        None => {
+            let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)?;
+            users
+                .insert(NewUser {
+                    username: &username,
+                    password_hash: &password_hash,
+                    public_access: true,
+                    readonly: false,
+                    demo: true,
+                })
+                .await?
+        }
+    };
+
+    let now = Utc::now();
+    let today = now.date_naive();
+    for sample in SAMPLE_TODOS {
+        let created_at = now - Duration::days(sample.days_ago);
+        let mut todo = todos
+            .insert(NewTodo {
+                user_id: user.id,
+                title: sample.title,
+                description: sample.description,
+                emoji: sample.emoji,
+                due_date: sample.due_in_days.map(|days| today + Duration::days(days)),
+                created_at,
+            })
+            .await?;
+        if sample.completed {
+            todo.completed = true;
+            todo.updated_at = created_at + Duration::hours(2);
+            todos.update(&todo).await?;
+        }
+    }
+
+    Ok(user)
+}
+
+// 按配置的间隔定时重置演示数据
+pub fn spawn_periodic_reset(storage: Arc<dyn Storage>, config: DemoConfig) {
+    if config.reset_interval_minutes == 0 {
+        return;
+    }
+    // 加载配置时已检查过，这里防止直接构造的配置
+    if let Err(e) = config.validate() {
+        warn!("{}，演示数据不会定期重置", e);
+        return;
+    }
+    info!("演示模式: 每 {} 分钟重置演示数据", config.reset_interval_minutes);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.reset_interval_minutes * 60));
+        // 第一次tick会立即触发，启动时已经重置过
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match reset(storage.as_ref(), &config).await {
+                Ok(_) => info!("演示数据已重置"),
+                Err(e) => error!("重置演示数据失败: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,178 @@
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    extract::{FromRequest, FromRequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{i18n, models::ApiResponse, validation::FieldError};
+
+// 稳定的错误码，客户端应按错误码而不是提示文字判断错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidRequest,
+    ValidationFailed,
+    Unauthorized,
+    InvalidCredentials,
+    ReadonlyUser,
+    NotFound,
+    UserNotFound,
+    TodoNotFound,
+    UsernameTaken,
+    SetupRequired,
+    SetupCompleted,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::ValidationFailed => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::Unauthorized | ErrorCode::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ErrorCode::ReadonlyUser => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound | ErrorCode::UserNotFound | ErrorCode::TodoNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::UsernameTaken | ErrorCode::SetupRequired | ErrorCode::SetupCompleted => StatusCode::CONFLICT,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    // 消息目录中的键，与序列化后的错误码一致
+    fn message_key(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "error.INVALID_REQUEST",
+            ErrorCode::ValidationFailed => "error.VALIDATION_FAILED",
+            ErrorCode::Unauthorized => "error.UNAUTHORIZED",
+            ErrorCode::InvalidCredentials => "error.INVALID_CREDENTIALS",
+            ErrorCode::ReadonlyUser => "error.READONLY_USER",
+            ErrorCode::NotFound => "error.NOT_FOUND",
+            ErrorCode::UserNotFound => "error.USER_NOT_FOUND",
+            ErrorCode::TodoNotFound => "error.TODO_NOT_FOUND",
+            ErrorCode::UsernameTaken => "error.USERNAME_TAKEN",
+            ErrorCode::SetupRequired => "error.SETUP_REQUIRED",
+            ErrorCode::SetupCompleted => "error.SETUP_COMPLETED",
+            ErrorCode::InternalError => "error.INTERNAL_ERROR",
+        }
+    }
+
+    // 按当前请求的语言返回错误提示
+    pub fn message(self) -> String {
+        i18n::t(self.message_key())
+    }
+}
+
+// 所有handler统一的错误类型，响应格式与ApiResponse一致
+#[derive(Debug)]
+pub struct AppError {
+    code: ErrorCode,
+    // 附加说明，追加在错误提示之后返回给客户端
+    detail: Option<String>,
+    // 参数校验失败的字段
+    fields: Vec<FieldError>,
+    // 内部错误的原因，只记录日志，不返回给客户端
+    source: Option<anyhow::Error>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode) -> Self {
+        Self {
+            code,
+            detail: None,
+            fields: Vec::new(),
+            source: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn validation(fields: Vec<FieldError>) -> Self {
+        Self {
+            fields,
+            ..Self::new(ErrorCode::ValidationFailed)
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl From<ErrorCode> for AppError {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code)
+    }
+}
+
+// 数据库等内部错误，可以直接用 ? 转换
+impl From<anyhow::Error> for AppError {
+    fn from(source: anyhow::Error) -> Self {
+        Self {
+            code: ErrorCode::InternalError,
+            detail: None,
+            fields: Vec::new(),
+            source: Some(source),
+        }
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(ErrorCode::InvalidRequest).with_detail(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::new(ErrorCode::InvalidRequest).with_detail(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(ErrorCode::InvalidRequest).with_detail(rejection.body_text())
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        if let Some(source) = &self.source {
+            error!("{:#}", source);
+        }
+
+        let message = match self.detail {
+            Some(detail) => format!("{}: {}", self.code.message(), detail),
+            None => self.code.message(),
+        };
+        let mut body = ApiResponse::<()>::error(self.code, message);
+        if !self.fields.is_empty() {
+            body.errors = Some(self.fields);
+        }
+        (self.code.status(), Json(body)).into_response()
+    }
+}
+
+// 请求体解析失败时也返回统一的错误格式
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct AppJson<T>(pub T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct AppPath<T>(pub T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct AppQuery<T>(pub T);
+
+// 未定义的API路径
+pub async fn api_not_found() -> AppError {
+    AppError::new(ErrorCode::NotFound)
+}
@@ -0,0 +1,223 @@
+use axum::{
+    extract::{Request, State},
+    handler::Handler,
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use std::{path::{Path, PathBuf}, sync::Arc};
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::{error, info};
+
+use crate::config::FrontendConfig;
+
+// Vite构建产物中文件名带内容哈希的目录，可以长期缓存
+const ASSETS_PREFIX: &str = "/assets/";
+// 静态资源的扩展名，缺失时返回404而不是前端页面
+const ASSET_EXTENSIONS: &[&str] = &[
+    "js", "mjs", "css", "map", "html", "json", "webmanifest", "txt", "xml", "wasm", "ico", "png", "jpg", "jpeg",
+    "gif", "svg", "webp", "avif", "woff", "woff2", "ttf", "otf", "eot", "mp3", "mp4", "webm",
+];
+
+// 构建前端路由，作为API路由之外所有请求的fallback
+pub fn router(config: &FrontendConfig) -> anyhow::Result<Option<Router>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let router = if config.embedded {
+        embedded_router()?
+    } else {
+        directory_router(Path::new(&config.directory))?
+    };
+
+    Ok(Some(
+        router
+            .layer(middleware::from_fn(cache_control)),
+    ))
+}
+
+fn directory_router(directory: &Path) -> anyhow::Result<Router> {
+    let index = directory.join("index.html");
+    if !index.is_file() {
+        return Err(anyhow::anyhow!(
+            "前端目录 {} 中没有index.html，请先构建前端（npm run build）",
+            directory.display()
+        ));
+    }
+    info!("提供前端静态文件: {}", directory.display());
+
+    let serve_dir = ServeDir::new(directory)
+        .precompressed_br()
+        .precompressed_gzip()
+        .fallback(spa_index.with_state(Arc::new(index)));
+    Ok(Router::new().fallback_service(serve_dir))
+}
+
+// 文件不存在时：静态资源返回404，其余交给前端路由
+async fn spa_index(State(index): State<Arc<PathBuf>>, request: Request) -> Response {
+    if !is_client_route(request.uri().path()) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match ServeFile::new(index.as_path())
+        .precompressed_br()
+        .precompressed_gzip()
+        .try_call(request)
+        .await
+    {
+        Ok(response) => response.into_response(),
+        Err(e) => {
+            error!("读取 {} 失败: {}", index.display(), e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+// 资源目录下的路径和常见静态文件扩展名视为资源；/user/john.doe、/tag/v1.2 这样带点的路径仍然是前端路由
+fn is_client_route(path: &str) -> bool {
+    if path.starts_with(ASSETS_PREFIX) {
+        return false;
+    }
+    let name = path.rsplit('/').next().unwrap_or_default();
+    !name.rsplit_once('.').is_some_and(|(_, extension)| {
+        ASSET_EXTENSIONS.iter().any(|known| extension.eq_ignore_ascii_case(known))
+    })
+}
+
+// 带哈希的资源永久缓存；index.html等每次都需向服务器确认
+async fn cache_control(request: Request, next: Next) -> Response {
+    let immutable = request.uri().path().starts_with(ASSETS_PREFIX);
+    let mut response = next.run(request).await;
+
+    if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+        let value = if immutable {
+            "public, max-age=31536000, immutable"
+        } else {
+            "no-cache"
+        };
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static(value));
+    }
+    response
+}
+
+#[cfg(not(feature = "embed-frontend"))]
+fn embedded_router() -> anyhow::Result<Router> {
+    Err(anyhow::anyhow!(
+        "frontend.embedded 需要以 embed-frontend 特性编译: cargo build --release --features embed-frontend"
+    ))
+}
+
+#[cfg(feature = "embed-frontend")]
+fn embedded_router() -> anyhow::Result<Router> {
+    embedded::router()
+}
+
+#[cfg(feature = "embed-frontend")]
+mod embedded {
+    use axum::{
+        body::Body,
+        http::{header, HeaderMap, Method, StatusCode, Uri},
+        response::{IntoResponse, Response},
+        Router,
+    };
+    use base64::{engine::general_purpose, Engine as _};
+    use rust_embed::{EmbeddedFile, RustEmbed};
+    use tracing::info;
+
+    use super::is_client_route;
+
+    #[derive(RustEmbed)]
+    #[folder = "../frontend/dist/"]
+    #[allow_missing = true]
+    struct Assets;
+
+    pub fn router() -> anyhow::Result<Router> {
+        if Assets::get("index.html").is_none() {
+            return Err(anyhow::anyhow!(
+                "二进制中没有内嵌前端资源，请先构建前端再以 embed-frontend 特性编译"
+            ));
+        }
+        info!("提供内嵌的前端资源");
+
+        Ok(Router::new().fallback(serve))
+    }
+
+    async fn serve(method: Method, uri: Uri, headers: HeaderMap) -> Response {
+        if method != Method::GET && method != Method::HEAD {
+            return StatusCode::METHOD_NOT_ALLOWED.into_response();
+        }
+
+        let path = uri.path().trim_start_matches('/');
+        let path = if path.is_empty() || path.ends_with('/') {
+            format!("{}index.html", path)
+        } else {
+            path.to_string()
+        };
+
+        let path = if Assets::get(&path).is_some() {
+            path
+        } else if is_client_route(uri.path()) {
+            "index.html".to_string()
+        } else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        respond(&path, &headers)
+    }
+
+    fn respond(path: &str, headers: &HeaderMap) -> Response {
+        let accept_encoding = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        // 优先返回构建时生成的预压缩文件
+        let (file, encoding) = [("br", ".br"), ("gzip", ".gz")]
+            .into_iter()
+            .filter(|(encoding, _)| accepts(accept_encoding, encoding))
+            .find_map(|(encoding, suffix)| {
+                Assets::get(&format!("{}{}", path, suffix)).map(|file| (file, Some(encoding)))
+            })
+            .or_else(|| Assets::get(path).map(|file| (file, None)))
+            .expect("调用方已确认文件存在");
+
+        let etag = etag(&file);
+        if headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == etag)
+        {
+            return StatusCode::NOT_MODIFIED.into_response();
+        }
+
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        let mut response = Response::builder()
+            .header(header::CONTENT_TYPE, mime.as_ref())
+            .header(header::ETAG, etag)
+            .header(header::VARY, "accept-encoding");
+        if let Some(encoding) = encoding {
+            response = response.header(header::CONTENT_ENCODING, encoding);
+        }
+        response
+            .body(Body::from(file.data))
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+    }
+
+    fn accepts(accept_encoding: &str, encoding: &str) -> bool {
+        accept_encoding.split(',').any(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            parts.next() == Some(encoding) && !parts.any(|param| param == "q=0")
+        })
+    }
+
+    fn etag(file: &EmbeddedFile) -> String {
+        format!(
+            "\"{}\"",
+            general_purpose::URL_SAFE_NO_PAD.encode(file.metadata.sha256_hash())
+        )
+    }
+}
@@ -1,17 +1,24 @@
+use anyhow::Context;
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::{HeaderMap, StatusCode},
     response::Json,
 };
 use chrono::Utc;
-use sqlx::Row;
-use tracing::{error, info};
+use tracing::{info, warn};
 use sha2::{Sha256, Digest};
 use base64::{Engine as _, engine::general_purpose};
 
 use crate::{
+    bootstrap,
+    error::{AppPath, AppResult, ErrorCode},
+    i18n::{self, Locale},
+    logging,
+    metrics,
     models::*,
-    AppState,
+    repository::{NewTodo, NewUser, TodoCursor, UserRepository},
+    validation::{parse_date, ValidJson, ValidQuery},
+    app::AppState,
 };
 
 // Token生成函数
@@ -30,515 +37,478 @@ fn verify_token(token: &str, username: &str, password_hash: &str) -> bool {
     token == expected_token
 }
 
-// 从请求头中提取用户ID
-async fn extract_user_id(headers: &HeaderMap, db: &sqlx::SqlitePool) -> Result<i64, StatusCode> {
+// 根据请求头中的token找到当前用户
+async fn authenticate(headers: &HeaderMap, users: &dyn UserRepository) -> AppResult<User> {
     let auth_header = headers.get("Authorization")
         .and_then(|h| h.to_str().ok())
         .and_then(|h| h.strip_prefix("Bearer "))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .ok_or(ErrorCode::Unauthorized)?;
 
     // 查询所有用户，验证token
-    let users_result = sqlx::query_as::<_, User>(
-        "SELECT id, username, password_hash, public_access, readonly, created_at FROM users"
-    )
-    .fetch_all(db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let users_result = users.list().await.context("查询用户列表失败")?;
 
     for user in users_result {
         if verify_token(auth_header, &user.username, &user.password_hash) {
-            return Ok(user.id);
+            logging::record_user_id(user.id);
+            use_preferred_locale(&user);
+            return Ok(user);
         }
     }
 
-    Err(StatusCode::UNAUTHORIZED)
+    Err(ErrorCode::Unauthorized.into())
 }
 
-// 检查用户是否为只读用户
-async fn check_readonly_permission(headers: &HeaderMap, db: &sqlx::SqlitePool) -> Result<(), StatusCode> {
-    let auth_header = headers.get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|h| h.strip_prefix("Bearer "))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+// 用户设置过语言偏好时，优先于请求头的Accept-Language
+fn use_preferred_locale(user: &User) {
+    if let Some(locale) = user.locale.as_deref().and_then(Locale::parse) {
+        i18n::set_current(locale);
+    }
+}
 
-    // 查询所有用户，验证token并检查readonly状态
-    let users_result = sqlx::query_as::<_, User>(
-        "SELECT id, username, password_hash, public_access, readonly, created_at FROM users"
-    )
-    .fetch_all(db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+// 从请求头中提取用户ID
+async fn extract_user_id(headers: &HeaderMap, users: &dyn UserRepository) -> AppResult<i64> {
+    authenticate(headers, users).await.map(|user| user.id)
+}
 
-    for user in users_result {
-        if verify_token(auth_header, &user.username, &user.password_hash) {
-            if user.readonly {
-                return Err(StatusCode::FORBIDDEN);
-            }
-            return Ok(());
-        }
+// 检查用户是否为只读用户，返回可写用户的ID
+async fn check_readonly_permission(headers: &HeaderMap, users: &dyn UserRepository) -> AppResult<i64> {
+    let user = authenticate(headers, users).await?;
+    if user.readonly {
+        return Err(ErrorCode::ReadonlyUser.into());
+    }
+    Ok(user.id)
+}
+
+// 确定当前查看的用户：已登录用户优先，否则回退到第一个公开访问的用户
+async fn resolve_viewer_id(headers: &HeaderMap, users: &dyn UserRepository) -> AppResult<Option<i64>> {
+    // 首先尝试从token获取用户ID
+    match extract_user_id(headers, users).await {
+        Ok(user_id) => return Ok(Some(user_id)),
+        Err(e) if e.code() != ErrorCode::Unauthorized => return Err(e),
+        Err(_) => {}
     }
 
-    Err(StatusCode::UNAUTHORIZED)
+    // 用户未登录，检查是否有公开访问的用户
+    let user = users.first_public().await.context("查询公开访问用户失败")?;
+    Ok(user.map(|user| user.id))
 }
 
 // 获取公开访问状态
 pub async fn get_public_access_handler(
     State(state): State<AppState>,
-) -> Result<Json<PublicAccessResponse>, StatusCode> {
+) -> AppResult<Json<PublicAccessResponse>> {
     // 简化版本：获取第一个用户的公开访问设置
-    let user_result = sqlx::query_as::<_, User>(
-        "SELECT id, username, password_hash, public_access, readonly, created_at FROM users LIMIT 1"
-    )
-    .fetch_optional(&state.db)
-    .await;
-
-    match user_result {
-        Ok(Some(user)) => {
-            let response = PublicAccessResponse {
-                public_access: user.public_access,
-                username: user.username,
-            };
-            Ok(Json(response))
-        }
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            error!("获取公开访问状态失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    let user = state
+        .storage
+        .users()
+        .first()
+        .await
+        .context("获取公开访问状态失败")?
+        .ok_or(ErrorCode::UserNotFound)?;
+
+    Ok(Json(PublicAccessResponse {
+        public_access: user.public_access,
+        username: user.username,
+    }))
 }
 
 // 更新用户设置
 pub async fn update_user_settings_handler(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<UpdateUserSettingsRequest>,
-) -> Result<Json<ApiResponse<UserResponse>>, StatusCode> {
+    ValidJson(payload): ValidJson<UpdateUserSettingsRequest>,
+) -> AppResult<Json<ApiResponse<UserResponse>>> {
     // 检查只读权限
-    check_readonly_permission(&headers, &state.db).await?;
-    
-    let user_id = extract_user_id(&headers, &state.db).await?;
-    
-    let result = sqlx::query(
-        "UPDATE users SET public_access = ? WHERE id = ?"
-    )
-    .bind(payload.public_access)
-    .bind(user_id)
-    .execute(&state.db)
-    .await;
-
-    match result {
-        Ok(_) => {
-            // 获取更新后的用户信息
-            let user_result = sqlx::query_as::<_, User>(
-                "SELECT id, username, password_hash, public_access, readonly, created_at FROM users WHERE id = ?"
-            )
-            .bind(user_id)
-            .fetch_optional(&state.db)
-            .await;
-
-            match user_result {
-                Ok(Some(user)) => {
-                    info!("用户设置已更新: 公开访问 = {}", payload.public_access);
-                    Ok(Json(ApiResponse::success(user.into())))
-                }
-                Ok(None) => Err(StatusCode::NOT_FOUND),
-                Err(e) => {
-                    error!("获取用户信息失败: {}", e);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
-                }
-            }
-        }
-        Err(e) => {
-            error!("更新用户设置失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+    let user_id = check_readonly_permission(&headers, state.storage.users()).await?;
+
+    if let Some(public_access) = payload.public_access {
+        state
+            .storage
+            .users()
+            .set_public_access(user_id, public_access)
+            .await
+            .context("更新用户设置失败")?;
+    }
+    if let Some(locale) = &payload.locale {
+        let locale = Some(locale.as_str()).filter(|locale| !locale.is_empty());
+        state
+            .storage
+            .users()
+            .set_locale(user_id, locale)
+            .await
+            .context("更新用户设置失败")?;
     }
+
+    // 获取更新后的用户信息
+    let user = state
+        .storage
+        .users()
+        .find_by_id(user_id)
+        .await
+        .context("获取用户信息失败")?
+        .ok_or(ErrorCode::UserNotFound)?;
+
+    info!("用户设置已更新: 公开访问 = {:?}, 语言 = {:?}", payload.public_access, payload.locale);
+    // 本次响应就使用新的语言偏好
+    use_preferred_locale(&user);
+    Ok(Json(ApiResponse::success(user.into())))
 }
 
 // 用户登录
 pub async fn login_handler(
     State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, StatusCode> {
-    let user_result = sqlx::query_as::<_, User>(
-        "SELECT id, username, password_hash, public_access, readonly, created_at FROM users WHERE username = ?"
-    )
-    .bind(&payload.username)
-    .fetch_optional(&state.db)
-    .await;
-
-    match user_result {
-        Ok(Some(user)) => {
-            if bcrypt::verify(&payload.password, &user.password_hash).unwrap_or(false) {
-                let token = generate_token(&user.username, &user.password_hash);
-                info!("用户 {} 登录成功", payload.username);
-                Ok(Json(LoginResponse {
-                    user: user.into(),
-                    token,
-                    message: "登录成功".to_string(),
-                }))
-            } else {
-                error!("用户 {} 密码错误", payload.username);
-                Err(StatusCode::UNAUTHORIZED)
-            }
-        }
-        Ok(None) => {
-            error!("用户 {} 不存在", payload.username);
-            Err(StatusCode::UNAUTHORIZED)
-        }
-        Err(e) => {
-            error!("数据库查询错误: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    ValidJson(payload): ValidJson<LoginRequest>,
+) -> AppResult<Json<LoginResponse>> {
+    let user = state
+        .storage
+        .users()
+        .find_by_username(&payload.username)
+        .await
+        .context("数据库查询错误")?;
+
+    let user = match user {
+        Some(user) => user,
+        None => {
+            metrics::record_login(false);
+            warn!("用户 {} 不存在", payload.username);
+            return Err(ErrorCode::InvalidCredentials.into());
         }
+    };
+
+    if !bcrypt::verify(&payload.password, &user.password_hash).unwrap_or(false) {
+        metrics::record_login(false);
+        warn!("用户 {} 密码错误", payload.username);
+        return Err(ErrorCode::InvalidCredentials.into());
     }
+
+    let token = generate_token(&user.username, &user.password_hash);
+    logging::record_user_id(user.id);
+    use_preferred_locale(&user);
+    metrics::record_login(true);
+    info!("用户 {} 登录成功", payload.username);
+    Ok(Json(LoginResponse {
+        user: user.into(),
+        token,
+        message: i18n::t("api.login_success"),
+    }))
 }
 
 // 用户注册
 pub async fn register_handler(
     State(state): State<AppState>,
-    Json(payload): Json<RegisterRequest>,
-) -> Result<Json<ApiResponse<UserResponse>>, StatusCode> {
+    ValidJson(payload): ValidJson<RegisterRequest>,
+) -> AppResult<Json<ApiResponse<UserResponse>>> {
+    // 第一个用户只能通过首次设置创建
+    if !bootstrap::setup_completed(state.storage.users())
+        .await
+        .context("数据库查询错误")?
+    {
+        return Err(ErrorCode::SetupRequired.into());
+    }
+
     // 检查用户名是否已存在
-    let existing_user = sqlx::query("SELECT id FROM users WHERE username = ?")
-        .bind(&payload.username)
-        .fetch_optional(&state.db)
-        .await;
-
-    match existing_user {
-        Ok(Some(_)) => {
-            return Ok(Json(ApiResponse::error("用户名已存在")));
-        }
-        Ok(None) => {}
-        Err(e) => {
-            error!("数据库查询错误: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    let existing_user = state
+        .storage
+        .users()
+        .find_by_username(&payload.username)
+        .await
+        .context("数据库查询错误")?;
+    if existing_user.is_some() {
+        return Err(ErrorCode::UsernameTaken.into());
     }
 
     // 创建新用户
-    let password_hash = match bcrypt::hash(&payload.password, bcrypt::DEFAULT_COST) {
-        Ok(hash) => hash,
-        Err(e) => {
-            error!("密码哈希错误: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
-    };
+    let password_hash = bcrypt::hash(&payload.password, bcrypt::DEFAULT_COST).context("密码哈希错误")?;
+
+    // 新注册用户默认不公开，且不是只读
+    let user = state
+        .storage
+        .users()
+        .insert(NewUser {
+            username: &payload.username,
+            password_hash: &password_hash,
+            public_access: false,
+            readonly: false,
+            demo: false,
+        })
+        .await
+        .context("创建用户失败")?;
+
+    info!("新用户注册: {}", payload.username);
+    Ok(Json(ApiResponse::success(user.into())))
+}
+
+// 首次设置状态：是否还没有任何用户，以及演示账户
+pub async fn get_setup_handler(State(state): State<AppState>) -> AppResult<Json<SetupStatusResponse>> {
+    let setup_required = bootstrap::setup_required(state.storage.users())
+        .await
+        .context("数据库查询错误")?;
 
-    let result = sqlx::query(
-        "INSERT INTO users (username, password_hash, public_access, readonly) VALUES (?, ?, ?, ?) RETURNING id, username, created_at"
+    Ok(Json(SetupStatusResponse {
+        setup_required,
+        demo: state.demo.as_deref().cloned(),
+    }))
+}
+
+// 首次设置：创建第一个用户并直接登录
+pub async fn setup_handler(
+    State(state): State<AppState>,
+    ValidJson(payload): ValidJson<SetupRequest>,
+) -> AppResult<Json<LoginResponse>> {
+    let user = bootstrap::setup(
+        state.storage.users(),
+        &payload.username,
+        &payload.password,
+        payload.public_access,
     )
-    .bind(&payload.username)
-    .bind(&password_hash)
-    .bind(false) // 新注册用户默认不公开
-    .bind(false) // 新注册用户默认不是只读
-    .fetch_one(&state.db)
-    .await;
-
-    match result {
-        Ok(row) => {
-            let user = UserResponse {
-                id: row.get("id"),
-                username: row.get("username"),
-                public_access: row.get("public_access"),
-                readonly: row.get("readonly"),
-            };
-            info!("新用户注册: {}", payload.username);
-            Ok(Json(ApiResponse::success(user)))
-        }
-        Err(e) => {
-            error!("创建用户失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    .await?
+    .ok_or(ErrorCode::SetupCompleted)?;
+
+    let token = generate_token(&user.username, &user.password_hash);
+    logging::record_user_id(user.id);
+    info!("首次设置完成，创建用户: {}", user.username);
+    Ok(Json(LoginResponse {
+        user: user.into(),
+        token,
+        message: i18n::t("api.setup_success"),
+    }))
 }
 
-// 获取任务列表
+// 获取任务列表：按创建时间倒序分页，支持筛选和搜索
 pub async fn get_todos_handler(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<Json<Vec<TodoResponse>>, StatusCode> {
-    // 首先尝试从token获取用户ID
-    let user_id_from_token = extract_user_id(&headers, &state.db).await.ok();
-    
-    let user_id = if let Some(uid) = user_id_from_token {
-        // 用户已登录，返回其任务
-        uid
-    } else {
-        // 用户未登录，检查是否有公开访问的用户
-        let user_result = sqlx::query_as::<_, User>(
-            "SELECT id, username, password_hash, public_access, readonly, created_at FROM users WHERE public_access = true LIMIT 1"
-        )
-        .fetch_optional(&state.db)
-        .await;
-
-        match user_result {
-            Ok(Some(user)) => user.id,
-            Ok(None) => {
-                // 没有公开访问的用户，返回空列表
-                return Ok(Json(Vec::new()));
-            }
-            Err(e) => {
-                error!("查询公开访问用户失败: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
+    ValidQuery(query): ValidQuery<TodoListQuery>,
+) -> AppResult<Json<Page<TodoResponse>>> {
+    let limit = query.page_limit();
+    let user_id = match resolve_viewer_id(&headers, state.storage.users()).await? {
+        Some(uid) => uid,
+        // 没有公开访问的用户，返回空列表
+        None => {
+            return Ok(Json(Page {
+                data: Vec::new(),
+                meta: PageMeta { total: 0, limit, next_cursor: None },
+            }))
         }
     };
 
-    // 获取该用户的任务
-    let todos_result = sqlx::query_as::<_, Todo>(
-        "SELECT id, user_id, title, description, emoji, completed, created_at, updated_at FROM todos WHERE user_id = ? ORDER BY created_at DESC"
-    )
-    .bind(user_id)
-    .fetch_all(&state.db)
-    .await;
-
-    match todos_result {
-        Ok(todos) => {
-            let todo_responses: Vec<TodoResponse> = todos.into_iter().map(|todo| todo.into()).collect();
-            Ok(Json(todo_responses))
-        }
-        Err(e) => {
-            error!("获取任务列表失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    let filter = query.filter();
+    let cursor = query.cursor.as_deref().and_then(TodoCursor::decode);
+    let todos = state.storage.todos();
+    // 多取一个用于判断是否还有下一页
+    let mut page = todos
+        .list_page(user_id, &filter, cursor.as_ref(), i64::from(limit) + 1)
+        .await
+        .context("获取任务列表失败")?;
+    let next_cursor = if page.len() > limit as usize {
+        page.truncate(limit as usize);
+        page.last().map(|todo| TodoCursor::after(todo).encode())
+    } else {
+        None
+    };
+    let total = todos.count(user_id, &filter).await.context("统计任务数量失败")?;
+
+    Ok(Json(Page {
+        data: page.into_iter().map(|todo| todo.into()).collect(),
+        meta: PageMeta { total, limit, next_cursor },
+    }))
+}
+
+// 全文搜索任务：按相关度排序，返回高亮后的标题和描述摘要
+pub async fn search_todos_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ValidQuery(query): ValidQuery<SearchQuery>,
+) -> AppResult<Json<Page<SearchHit>>> {
+    let limit = query.page_limit();
+    let user_id = match resolve_viewer_id(&headers, state.storage.users()).await? {
+        Some(uid) => uid,
+        None => {
+            return Ok(Json(Page {
+                data: Vec::new(),
+                meta: PageMeta { total: 0, limit, next_cursor: None },
+            }))
         }
-    }
+    };
+
+    let terms = query.terms();
+    let results = state
+        .storage
+        .todos()
+        .search(user_id, &terms, i64::from(limit))
+        .await
+        .context("搜索任务失败")?;
+
+    // 搜索结果按相关度排序，不支持游标翻页
+    Ok(Json(Page {
+        data: results.hits.into_iter().map(|hit| SearchHit::new(hit, &terms)).collect(),
+        meta: PageMeta { total: results.total, limit, next_cursor: None },
+    }))
 }
 
 // 创建新任务
 pub async fn create_todo_handler(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<CreateTodoRequest>,
-) -> Result<Json<TodoResponse>, StatusCode> {
+    ValidJson(payload): ValidJson<CreateTodoRequest>,
+) -> AppResult<Json<TodoResponse>> {
     // 检查只读权限
-    check_readonly_permission(&headers, &state.db).await?;
-    
-    let user_id = extract_user_id(&headers, &state.db).await?;
+    let user_id = check_readonly_permission(&headers, state.storage.users()).await?;
     let now = Utc::now();
-    
-    let result = sqlx::query(
-        "INSERT INTO todos (user_id, title, description, emoji, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING id"
-    )
-    .bind(user_id)
-    .bind(&payload.title)
-    .bind(&payload.description)
-    .bind(&payload.emoji)
-    .bind(now)
-    .bind(now)
-    .fetch_one(&state.db)
-    .await;
-
-    match result {
-        Ok(row) => {
-            let todo_id: i64 = row.get("id");
-            let todo = TodoResponse {
-                id: todo_id,
-                title: payload.title,
-                description: payload.description,
-                emoji: payload.emoji,
-                completed: false,
-                created_at: now,
-                updated_at: now,
-            };
-            info!("创建新任务: {}", todo.title);
-            Ok(Json(todo))
-        }
-        Err(e) => {
-            error!("创建任务失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+
+    let todo = state
+        .storage
+        .todos()
+        .insert(NewTodo {
+            user_id,
+            title: &payload.title,
+            description: payload.description.as_deref(),
+            emoji: &payload.emoji,
+            due_date: payload.due_date.as_deref().and_then(parse_date),
+            created_at: now,
+        })
+        .await
+        .context("创建任务失败")?;
+
+    info!("创建新任务: {}", todo.title);
+    Ok(Json(todo.into()))
 }
 
 // 更新任务
 pub async fn update_todo_handler(
-    Path(id): Path<i64>,
+    AppPath(id): AppPath<i64>,
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<UpdateTodoRequest>,
-) -> Result<Json<TodoResponse>, StatusCode> {
+    ValidJson(payload): ValidJson<UpdateTodoRequest>,
+) -> AppResult<Json<TodoResponse>> {
     // 检查只读权限
-    check_readonly_permission(&headers, &state.db).await?;
-    
-    let user_id = extract_user_id(&headers, &state.db).await?;
+    let user_id = check_readonly_permission(&headers, state.storage.users()).await?;
     let now = Utc::now();
-    
+
     // 获取当前任务
-    let current_todo = sqlx::query_as::<_, Todo>(
-        "SELECT id, user_id, title, description, emoji, completed, created_at, updated_at FROM todos WHERE id = ?"
-    )
-    .bind(id)
-    .fetch_optional(&state.db)
-    .await;
-
-    let mut todo = match current_todo {
-        Ok(Some(todo)) => todo,
-        Ok(None) => return Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            error!("查询任务失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
-    };
+    let mut todo = state
+        .storage
+        .todos()
+        .find_by_id(id, user_id)
+        .await
+        .context("查询任务失败")?
+        .ok_or(ErrorCode::TodoNotFound)?;
 
     // 更新字段
-    if let Some(title) = payload.title {
-        todo.title = title;
-    }
-    if let Some(description) = payload.description {
-        todo.description = Some(description);
-    }
-    if let Some(emoji) = payload.emoji {
-        todo.emoji = emoji;
-    }
-    if let Some(completed) = payload.completed {
-        todo.completed = completed;
-    }
-    todo.updated_at = now;
+    todo.apply_update(payload, now);
 
     // 保存更新
-    let result = sqlx::query(
-        "UPDATE todos SET title = ?, description = ?, emoji = ?, completed = ?, updated_at = ? WHERE id = ?"
-    )
-    .bind(&todo.title)
-    .bind(&todo.description)
-    .bind(&todo.emoji)
-    .bind(todo.completed)
-    .bind(now)
-    .bind(id)
-    .execute(&state.db)
-    .await;
-
-    match result {
-        Ok(_) => {
-            info!("更新任务: {} (ID: {})", todo.title, id);
-            Ok(Json(todo.into()))
-        }
-        Err(e) => {
-            error!("更新任务失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    state.storage.todos().update(&todo).await.context("更新任务失败")?;
+
+    info!("更新任务: {} (ID: {})", todo.title, id);
+    Ok(Json(todo.into()))
 }
 
 // 删除任务
 pub async fn delete_todo_handler(
-    Path(id): Path<i64>,
+    AppPath(id): AppPath<i64>,
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<StatusCode, StatusCode> {
+) -> AppResult<StatusCode> {
     // 检查只读权限
-    check_readonly_permission(&headers, &state.db).await?;
-    
-    let user_id = extract_user_id(&headers, &state.db).await?;
-    
-    let result = sqlx::query("DELETE FROM todos WHERE id = ? AND user_id = ?")
-        .bind(id)
-        .bind(user_id)
-        .execute(&state.db)
-        .await;
-
-    match result {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
-                info!("删除任务 ID: {}", id);
-                Ok(StatusCode::NO_CONTENT)
-            } else {
-                Err(StatusCode::NOT_FOUND)
-            }
-        }
-        Err(e) => {
-            error!("删除任务失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+    let user_id = check_readonly_permission(&headers, state.storage.users()).await?;
+
+    // 移到回收站，保留期限内可以恢复
+    let trashed = state
+        .storage
+        .todos()
+        .trash(id, user_id, Utc::now())
+        .await
+        .context("删除任务失败")?;
+    if !trashed {
+        return Err(ErrorCode::TodoNotFound.into());
     }
+
+    info!("任务 ID: {} 已移到回收站", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// 获取回收站中的任务，按删除时间倒序
+pub async fn get_trash_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<TrashResponse>> {
+    // 回收站只对本人可见，不回退到公开访问的用户
+    let user_id = extract_user_id(&headers, state.storage.users()).await?;
+
+    let todos = state
+        .storage
+        .todos()
+        .list_trash(user_id)
+        .await
+        .context("获取回收站失败")?;
+
+    Ok(Json(TrashResponse {
+        data: todos.into_iter().map(|todo| todo.into()).collect(),
+        retention_days: state.trash_retention_days,
+    }))
+}
+
+// 从回收站恢复任务
+pub async fn restore_todo_handler(
+    AppPath(id): AppPath<i64>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<TodoResponse>> {
+    // 检查只读权限
+    let user_id = check_readonly_permission(&headers, state.storage.users()).await?;
+
+    let todo = state
+        .storage
+        .todos()
+        .restore(id, user_id, Utc::now())
+        .await
+        .context("恢复任务失败")?
+        .ok_or(ErrorCode::TodoNotFound)?;
+
+    info!("从回收站恢复任务 ID: {}", id);
+    Ok(Json(todo.into()))
 }
 
 // 获取历史数据
 pub async fn get_history_handler(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<Json<Vec<HistoryDay>>, StatusCode> {
-    // 首先尝试从token获取用户ID
-    let user_id_from_token = extract_user_id(&headers, &state.db).await.ok();
-    
-    let user_id = if let Some(uid) = user_id_from_token {
-        // 用户已登录，返回其历史任务
-        uid
-    } else {
-        // 用户未登录，检查是否有公开访问的用户
-        let user_result = sqlx::query_as::<_, User>(
-            "SELECT id, username, password_hash, public_access, readonly, created_at FROM users WHERE public_access = true LIMIT 1"
-        )
-        .fetch_optional(&state.db)
-        .await;
-
-        match user_result {
-            Ok(Some(user)) => user.id,
-            Ok(None) => {
-                // 没有公开访问的用户，返回空列表
-                return Ok(Json(Vec::new()));
-            }
-            Err(e) => {
-                error!("查询公开访问用户失败: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        }
+) -> AppResult<Json<Vec<HistoryDay>>> {
+    let user_id = match resolve_viewer_id(&headers, state.storage.users()).await? {
+        Some(uid) => uid,
+        // 没有公开访问的用户，返回空列表
+        None => return Ok(Json(Vec::new())),
     };
 
     // 获取过去365天的数据，限制为指定用户
-    let result = sqlx::query(
-        r#"
-        SELECT 
-            DATE(created_at) as date,
-            COUNT(*) as count,
-            SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END) as completed_count
-        FROM todos 
-        WHERE user_id = ? AND created_at >= DATE('now', '-365 days')
-        GROUP BY DATE(created_at)
-        ORDER BY date
-        "#
-    )
-    .bind(user_id)
-    .fetch_all(&state.db)
-    .await;
-
-    match result {
-        Ok(rows) => {
-            let mut history_days = Vec::new();
-            
-            for row in rows {
-                let date: String = row.get("date");
-                let count: i64 = row.get("count");
-                let completed_count: i64 = row.get("completed_count");
-                
-                // 获取该日期的所有任务
-                let tasks_result = sqlx::query_as::<_, Todo>(
-                    "SELECT id, user_id, title, description, emoji, completed, created_at, updated_at FROM todos WHERE user_id = ? AND DATE(created_at) = ? ORDER BY created_at"
-                )
-                .bind(user_id)
-                .bind(&date)
-                .fetch_all(&state.db)
-                .await;
-                
-                let tasks = match tasks_result {
-                    Ok(todos) => todos.into_iter().map(|todo| todo.into()).collect(),
-                    Err(_) => Vec::new(),
-                };
-                
-                history_days.push(HistoryDay {
-                    date,
-                    count,
-                    completed_count,
-                    tasks,
-                });
-            }
-            
-            Ok(Json(history_days))
-        }
-        Err(e) => {
-            error!("获取历史数据失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+    let buckets = state
+        .storage
+        .todos()
+        .history_buckets(user_id, 365)
+        .await
+        .context("获取历史数据失败")?;
+
+    let mut history_days = Vec::new();
+    for bucket in buckets {
+        // 获取该日期的所有任务
+        let tasks = state
+            .storage
+            .todos()
+            .list_created_on(user_id, &bucket.date)
+            .await
+            .context("获取历史任务失败")?
+            .into_iter()
+            .map(|todo| todo.into())
+            .collect();
+
+        history_days.push(HistoryDay {
+            date: bucket.date,
+            count: bucket.count,
+            completed_count: bucket.completed_count,
+            tasks,
+        });
     }
-}
\ No newline at end of file
+
+    Ok(Json(history_days))
+}
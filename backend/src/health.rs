@@ -0,0 +1,113 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use anyhow::Context;
+use tracing::warn;
+
+use crate::{
+    config::Config,
+    error::AppResult,
+    migrations,
+    models::{HealthResponse, VersionResponse},
+    app::AppState,
+};
+
+// 探针和版本信息路由不经过CORS和请求日志，避免编排系统和监控的频繁探测刷屏
+pub fn probe_routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .route("/api/version", get(version_handler))
+}
+
+// 本次启动启用的功能：编译特性和配置开关
+pub fn enabled_features(config: &Config) -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "embed-frontend") {
+        features.push("embed-frontend");
+    }
+    if config.server.tls.enabled {
+        features.push("tls");
+    }
+    if !config.server.unix_socket.is_empty() {
+        features.push("unix-socket");
+    }
+    if config.frontend.enabled {
+        features.push("frontend");
+    }
+    if config.backup.enabled {
+        features.push("scheduled-backup");
+    }
+    if config.demo.enabled {
+        features.push("demo");
+    }
+    features
+}
+
+// 进程存活
+async fn healthz_handler() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        schema_version: None,
+        reason: None,
+    })
+}
+
+// 数据库可以访问且schema已是最新版本时才接收流量
+async fn readyz_handler(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let storage = state.storage.as_ref();
+    let unavailable = |reason: String| {
+        warn!("就绪检查失败: {}", reason);
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: "unavailable",
+                schema_version: None,
+                reason: Some(reason),
+            }),
+        )
+    };
+
+    if let Err(e) = storage.ping().await {
+        return unavailable(format!("数据库不可用: {}", e));
+    }
+
+    let current = match migrations::current_version(storage).await {
+        Ok(version) => version,
+        Err(e) => return unavailable(format!("无法读取schema版本: {}", e)),
+    };
+    let latest = migrations::latest_version(storage);
+    if current != latest {
+        return unavailable(format!("schema版本 {} 与程序版本 {} 不一致", current, latest));
+    }
+
+    (
+        StatusCode::OK,
+        Json(HealthResponse {
+            status: "ready",
+            schema_version: Some(current),
+            reason: None,
+        }),
+    )
+}
+
+// 版本信息，无需登录
+pub async fn version_handler(State(state): State<AppState>) -> AppResult<Json<VersionResponse>> {
+    let storage = state.storage.as_ref();
+    let schema_version = migrations::current_version(storage)
+        .await
+        .context("读取schema版本失败")?;
+
+    Ok(Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("DEMETER_GIT_COMMIT"),
+        storage: storage.backend_name(),
+        schema_version,
+        latest_schema_version: migrations::latest_version(storage),
+        features: state.features.to_vec(),
+    }))
+}
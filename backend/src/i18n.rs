@@ -0,0 +1,174 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use once_cell::sync::{Lazy, OnceCell};
+use std::{cell::Cell, collections::HashMap, fmt::Display};
+
+// 支持的语言，消息目录在编译期嵌入二进制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    ZhCn,
+    En,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::ZhCn, Locale::En];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::En => "en",
+        }
+    }
+
+    fn catalog_source(self) -> &'static str {
+        match self {
+            Locale::ZhCn => include_str!("../locales/zh-CN.toml"),
+            Locale::En => include_str!("../locales/en.toml"),
+        }
+    }
+
+    // 按主语言匹配，兼容 zh-Hans、en-US、zh_CN.UTF-8 等写法
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let tag = tag.trim().split(['.', '@']).next().unwrap_or_default();
+        let primary = tag.split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    // 按q值从高到低选择第一个支持的语言，如 "en-US,en;q=0.9,zh;q=0.8"
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut candidates: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map(|q| q.trim().parse().unwrap_or(0.0))
+                    .unwrap_or(1.0);
+                (quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        candidates.into_iter().find_map(|(tag, _)| Locale::parse(tag))
+    }
+
+    // 命令行使用终端的语言环境
+    pub fn from_env() -> Option<Locale> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::parse(&value))
+    }
+
+    pub fn supported_tags() -> String {
+        Locale::ALL.iter().map(|locale| locale.tag()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+// 消息目录按 "分组.键" 展开，如 error.NOT_FOUND
+static CATALOGS: Lazy<HashMap<Locale, HashMap<String, String>>> = Lazy::new(|| {
+    Locale::ALL
+        .iter()
+        .map(|&locale| {
+            let table: toml::Table = locale
+                .catalog_source()
+                .parse()
+                .unwrap_or_else(|e| panic!("消息目录 {} 格式错误: {}", locale.tag(), e));
+            let mut messages = HashMap::new();
+            flatten("", table, &mut messages);
+            (locale, messages)
+        })
+        .collect()
+});
+
+fn flatten(prefix: &str, table: toml::Table, messages: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, messages),
+            toml::Value::String(message) => {
+                messages.insert(key, message);
+            }
+            other => panic!("消息 {} 必须是字符串: {}", key, other),
+        }
+    }
+}
+
+static DEFAULT_LOCALE: OnceCell<Locale> = OnceCell::new();
+
+tokio::task_local! {
+    // 当前请求使用的语言，登录用户的语言偏好会覆盖请求头
+    static REQUEST_LOCALE: Cell<Locale>;
+}
+
+// 进程级默认语言，启动时设置一次
+pub fn set_default(locale: Locale) {
+    let _ = DEFAULT_LOCALE.set(locale);
+}
+
+pub fn default_locale() -> Locale {
+    DEFAULT_LOCALE.get().copied().unwrap_or_default()
+}
+
+// 请求处理中返回协商出的语言，其他场景（命令行）返回默认语言
+pub fn current() -> Locale {
+    REQUEST_LOCALE.try_with(Cell::get).unwrap_or_else(|_| default_locale())
+}
+
+// 在请求处理中切换语言，不在请求中时忽略
+pub fn set_current(locale: Locale) {
+    let _ = REQUEST_LOCALE.try_with(|current| current.set(locale));
+}
+
+pub fn t(key: &str) -> String {
+    t_args(key, &[])
+}
+
+// 查找当前语言的消息并替换 {name} 占位符；缺失时回退到简体中文，再回退到键名
+pub fn t_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate(current(), key, args)
+}
+
+pub fn translate(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let message = CATALOGS[&locale]
+        .get(key)
+        .or_else(|| CATALOGS[&Locale::ZhCn].get(key))
+        .map(String::as_str)
+        .unwrap_or(key);
+
+    let mut message = message.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+// 根据Accept-Language协商语言，并在响应中返回Content-Language
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+        .unwrap_or_else(default_locale);
+
+    REQUEST_LOCALE
+        .scope(Cell::new(locale), async move {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert(header::CONTENT_LANGUAGE, HeaderValue::from_static(current().tag()));
+            response
+        })
+        .await
+}
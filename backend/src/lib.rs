@@ -0,0 +1,31 @@
+// Demeter核心库：服务端（demeter）、管理工具（demeter-admin）和客户端（demeter-cli、demeter-tui）共用，
+// 也可以通过 build_router 把API嵌入到其他Axum应用中
+pub mod app;
+pub mod backup;
+pub mod bootstrap;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod config;
+pub mod cors;
+pub mod database;
+pub mod demo;
+pub mod error;
+pub mod frontend;
+pub mod handlers;
+pub mod health;
+pub mod i18n;
+pub mod logging;
+pub mod migrations;
+pub mod models;
+pub mod repository;
+pub mod search;
+pub mod server;
+pub mod storage;
+pub mod trash;
+pub mod validation;
+
+mod metrics;
+mod tls;
+
+pub use app::{build_router, serve, AppState};
+pub use config::Config;
@@ -0,0 +1,122 @@
+use axum::{body::Body, http::Request, response::Response};
+use std::{fs, time::Duration};
+use tracing::{field, info, Span, Subscriber};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    field::RecordFields,
+    fmt::{
+        self,
+        format::{DefaultFields, Writer},
+        FormatFields, MakeWriter,
+    },
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+use crate::config::{LogFileConfig, LogFormat, LogRotation, LoggingConfig};
+
+// 初始化日志：标准输出，以及可选的滚动日志文件
+// 返回的guard需要一直持有，drop时会把缓冲中的日志写入文件
+pub fn init(config: &LoggingConfig) -> anyhow::Result<Option<WorkerGuard>> {
+    let filter = EnvFilter::try_new(&config.level)
+        .map_err(|e| anyhow::anyhow!("日志级别配置错误 \"{}\": {}", config.level, e))?;
+
+    let (file_layer, guard) = if config.file.enabled {
+        let (writer, guard) = tracing_appender::non_blocking(file_appender(&config.file)?);
+        (Some(fmt_layer(config.format, writer, false)), Some(guard))
+    } else {
+        (None, None)
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(config.format, std::io::stdout, true))
+        .with(file_layer)
+        .try_init()?;
+
+    Ok(guard)
+}
+
+fn file_appender(config: &LogFileConfig) -> anyhow::Result<RollingFileAppender> {
+    let rotation = match config.rotation {
+        LogRotation::Minutely => Rotation::MINUTELY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Never => Rotation::NEVER,
+    };
+
+    // 目录不存在时清理旧文件会报错，提前创建
+    fs::create_dir_all(&config.directory)
+        .map_err(|e| anyhow::anyhow!("无法创建日志目录 {}: {}", config.directory, e))?;
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(&config.prefix);
+    if config.max_files > 0 {
+        builder = builder.max_log_files(config.max_files);
+    }
+
+    builder
+        .build(&config.directory)
+        .map_err(|e| anyhow::anyhow!("无法创建日志文件 {}: {}", config.directory, e))
+}
+
+fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Text if ansi => layer.boxed(),
+        LogFormat::Text => layer.fmt_fields(PlainFields::default()).boxed(),
+        // JSON中只带当前请求span的字段，便于日志系统按request_id聚合
+        LogFormat::Json => layer.json().with_current_span(true).with_span_list(false).boxed(),
+    }
+}
+
+// span字段格式化后按格式化器类型缓存在span上；文件层使用独立类型，
+// 避免与标准输出层共用缓存导致字段重复记录、ANSI转义写入文件
+#[derive(Default)]
+struct PlainFields(DefaultFields);
+
+impl<'w> FormatFields<'w> for PlainFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'w>, fields: R) -> std::fmt::Result {
+        self.0.format_fields(writer, fields)
+    }
+}
+
+// 每个请求一个span；user_id在鉴权成功后由handler记录，status/latency在响应时记录
+pub fn make_request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        user_id = field::Empty,
+        status = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+pub fn on_response(response: &Response, latency: Duration, span: &Span) {
+    span.record("status", response.status().as_u16());
+    span.record("latency_ms", latency.as_millis() as u64);
+    info!("请求完成");
+}
+
+// 在当前请求span中记录已认证的用户
+pub fn record_user_id(user_id: i64) {
+    Span::current().record("user_id", user_id);
+}
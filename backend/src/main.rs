@@ -1,60 +1,144 @@
-use axum::{
-    http::{HeaderValue, Method, header::{CONTENT_TYPE, AUTHORIZATION}},
-    routing::{get, post, put},
-    Router,
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use demeter::{
+    backup, config,
+    database::connect_database,
+    i18n::{self, t, t_args, Locale},
+    logging, migrations, Config,
 };
-use sqlx::sqlite::SqlitePool;
-use tower_http::cors::{CorsLayer};
-use tracing::info;
 
-mod models;
-mod handlers;
-mod database;
-mod config;
+#[derive(Parser)]
+#[command(name = "demeter", version, about = "Demeter TodoList 后端服务")]
+struct Cli {
+    /// 配置文件路径，默认读取当前目录的config.toml（不存在时使用内置默认值）
+    #[arg(long, global = true, env = config::CONFIG_PATH_ENV)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 启动HTTP服务（默认）
+    Serve,
+    /// 管理数据库schema迁移
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateCommand,
+    },
+    /// 在线备份数据库到指定文件（服务运行时也可安全执行，仅SQLite）
+    Backup {
+        /// 备份文件路径；省略时写入配置的备份目录并按保留数量清理旧备份
+        path: Option<PathBuf>,
+    },
+    /// 从备份文件恢复数据库（请先停止服务，仅SQLite）
+    Restore {
+        /// 备份文件路径
+        path: PathBuf,
+    },
+    /// 查看配置
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
 
-use database::*;
-use config::Config;
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// 输出合并默认值、配置文件和环境变量后生效的配置
+    Print,
+}
 
-#[derive(Clone)]
-pub struct AppState {
-    db: SqlitePool,
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// 查看迁移状态
+    Status,
+    /// 应用所有未执行的迁移
+    Up,
+    /// 回滚最近的迁移
+    Down {
+        /// 回滚的迁移数量
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // 加载配置文件
-    let config = Config::load()?;
-    
+    // .env中的变量不会覆盖已设置的环境变量；文件不存在时忽略
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+
+    // 加载配置：默认值 -> 配置文件 -> DEMETER_* 环境变量
+    let config = Config::load(cli.config.as_deref())?;
+
     // 初始化日志
-    tracing_subscriber::fmt::init();
-
-    // 初始化数据库
-    let db = init_database(&config.database).await?;
-    let state = AppState { db };
-
-    // 配置CORS
-    let cors = CorsLayer::new()
-        .allow_origin(config.server.cors_origin.parse::<HeaderValue>()?)
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
-
-    // 构建路由
-    let app = Router::new()
-        .route("/api/login", post(handlers::login_handler))
-        .route("/api/register", post(handlers::register_handler))
-        .route("/api/todos", get(handlers::get_todos_handler).post(handlers::create_todo_handler))
-        .route("/api/todos/:id", put(handlers::update_todo_handler).delete(handlers::delete_todo_handler))
-        .route("/api/history", get(handlers::get_history_handler))
-        .route("/api/public-access", get(handlers::get_public_access_handler))
-        .route("/api/user/settings", put(handlers::update_user_settings_handler))
-        .layer(cors)
-        .with_state(state);
-
-    info!("服务器启动在 {}", config.server_url());
-
-    // 启动服务器
-    let listener = tokio::net::TcpListener::bind(&config.server_address()).await?;
-    axum::serve(listener, app).await?;
+    let _log_guard = logging::init(&config.logging)?;
+    config.warn_ignored_env_vars();
+
+    // 服务端使用配置的默认语言；命令行输出优先使用终端的语言环境
+    let command = cli.command.unwrap_or(Command::Serve);
+    let default_locale = config.i18n.default_locale()?;
+    i18n::set_default(match command {
+        Command::Serve => default_locale,
+        _ => Locale::from_env().unwrap_or(default_locale),
+    });
+
+    match command {
+        Command::Serve => demeter::serve(config).await,
+        Command::Migrate { action } => migrate(config, action).await,
+        Command::Backup { path } => run_backup(config, path).await,
+        Command::Restore { path } => run_restore(config, path).await,
+        Command::Config { action: ConfigCommand::Print } => {
+            print!("{}", config.to_toml()?);
+            Ok(())
+        }
+    }
+}
 
+async fn migrate(config: Config, action: MigrateCommand) -> anyhow::Result<()> {
+    let storage = connect_database(&config.database).await?;
+    let storage = storage.as_ref();
+
+    match action {
+        MigrateCommand::Status => migrations::print_status(storage).await?,
+        MigrateCommand::Up => migrations::print_applied(&migrations::migrate_up(storage).await?),
+        MigrateCommand::Down { steps } => {
+            let reverted = migrations::migrate_down(storage, steps).await?;
+            println!("{}", t_args("cli.migrate_reverted", &[
+                ("count", &reverted.len()),
+                ("versions", &format!("{:?}", reverted)),
+            ]));
+        }
+    }
+
+    storage.close().await;
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn run_backup(config: Config, path: Option<PathBuf>) -> anyhow::Result<()> {
+    let storage = connect_database(&config.database).await?;
+
+    let result = match path {
+        Some(path) => backup::backup_to(storage.as_ref(), &path).await.map(|_| path),
+        None => backup::backup_with_retention(storage.as_ref(), &config.backup).await,
+    };
+    storage.close().await;
+
+    let path = result?;
+    println!("{}", t_args("cli.backup_done", &[("path", &path.display())]));
+    Ok(())
+}
+
+async fn run_restore(config: Config, path: PathBuf) -> anyhow::Result<()> {
+    let target = backup::restore(&config.database, &path).await?;
+    println!("{}", t_args("cli.restore_done", &[
+        ("source", &path.display()),
+        ("target", &target.display()),
+    ]));
+    println!("{}", t("cli.restore_hint"));
+    Ok(())
+}
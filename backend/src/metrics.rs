@@ -0,0 +1,114 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::{sync::Arc, time::Instant};
+use tracing::{error, info};
+
+use crate::{config::MetricsConfig, storage::Storage};
+
+// 请求和数据库耗时的直方图分桶（秒）
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Clone)]
+struct MetricsState {
+    handle: PrometheusHandle,
+    storage: Arc<dyn Storage>,
+}
+
+// 安装全局指标记录器；未安装时各处的指标宏都是空操作
+pub fn install() -> anyhow::Result<PrometheusHandle> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("duration_seconds".to_string()), DURATION_BUCKETS)?
+        .install_recorder()
+        .map_err(|e| anyhow::anyhow!("无法初始化指标记录器: {}", e))
+}
+
+pub fn routes(handle: PrometheusHandle, storage: Arc<dyn Storage>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(MetricsState { handle, storage })
+}
+
+// 在独立端口提供 /metrics，便于只在内网开放
+pub fn spawn_admin_server(config: &MetricsConfig, routes: Router) {
+    let address = format!("{}:{}", config.admin_host, config.admin_port);
+
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(&address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("指标端口 {} 监听失败: {}", address, e);
+                return;
+            }
+        };
+
+        info!("指标服务监听在 http://{}/metrics", address);
+        if let Err(e) = axum::serve(listener, routes).await {
+            error!("指标服务异常退出: {}", e);
+        }
+    });
+}
+
+async fn metrics_handler(State(state): State<MetricsState>) -> Response {
+    refresh_gauges(state.storage.as_ref()).await;
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.handle.render(),
+    )
+        .into_response()
+}
+
+// 连接池和业务数据的gauge在抓取时更新
+async fn refresh_gauges(storage: &dyn Storage) {
+    if let Some(stats) = storage.pool_stats() {
+        gauge!("demeter_db_pool_connections", "state" => "idle").set(stats.idle as f64);
+        gauge!("demeter_db_pool_connections", "state" => "active").set(stats.size.saturating_sub(stats.idle) as f64);
+        gauge!("demeter_db_pool_max_connections").set(stats.max as f64);
+    }
+
+    match storage.todos().totals().await {
+        Ok(totals) => {
+            gauge!("demeter_todos", "status" => "open").set(totals.open as f64);
+            gauge!("demeter_todos", "status" => "completed").set(totals.completed as f64);
+        }
+        Err(e) => error!("统计任务数量失败: {}", e),
+    }
+}
+
+// 按路由模板（而不是实际路径）统计请求，避免标签数量随ID增长
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "fallback".to_string());
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!("demeter_http_requests_total", &labels).increment(1);
+    histogram!("demeter_http_request_duration_seconds", &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+pub fn record_login(success: bool) {
+    let result = if success { "success" } else { "failure" };
+    counter!("demeter_logins_total", "result" => result).increment(1);
+}
@@ -0,0 +1,202 @@
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+use crate::{
+    i18n::{t, t_args},
+    storage::Storage,
+};
+
+// 单个版本化迁移，SQL文件在编译期嵌入二进制
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+// 已记录在schema_migrations表中的迁移
+#[derive(Debug)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+macro_rules! migration {
+    ($backend:literal, $version:expr, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!("../migrations/", $backend, "/", $name, ".up.sql")),
+            down: include_str!(concat!("../migrations/", $backend, "/", $name, ".down.sql")),
+        }
+    };
+}
+
+// 各存储后端的迁移，按版本号升序排列；新增迁移只能追加在末尾，且两个后端保持相同的版本号
+pub static SQLITE_MIGRATIONS: &[Migration] = &[
+    migration!("sqlite", 1, "0001_initial"),
+    migration!("sqlite", 2, "0002_user_locale"),
+    migration!("sqlite", 3, "0003_todo_due_date"),
+    migration!("sqlite", 4, "0004_todo_search"),
+    migration!("sqlite", 5, "0005_todo_trash"),
+    migration!("sqlite", 6, "0006_user_demo"),
+];
+
+pub static POSTGRES_MIGRATIONS: &[Migration] = &[
+    migration!("postgres", 1, "0001_initial"),
+    migration!("postgres", 2, "0002_user_locale"),
+    migration!("postgres", 3, "0003_todo_due_date"),
+    migration!("postgres", 4, "0004_todo_search"),
+    migration!("postgres", 5, "0005_todo_trash"),
+    migration!("postgres", 6, "0006_user_demo"),
+];
+
+// 迁移状态（用于命令行展示）
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: Option<DateTime<Utc>>,
+    // 数据库中已应用、但当前程序不认识的迁移
+    pub unknown: bool,
+}
+
+pub fn latest_version(storage: &dyn Storage) -> i64 {
+    storage.migrations().last().map(|m| m.version).unwrap_or(0)
+}
+
+// 获取数据库当前的schema版本，未执行过任何迁移时为0
+pub async fn current_version(storage: &dyn Storage) -> anyhow::Result<i64> {
+    let applied = storage.applied_migrations().await?;
+    Ok(applied.last().map(|m| m.version).unwrap_or(0))
+}
+
+// 执行所有未应用的迁移，返回本次应用的版本号
+pub async fn migrate_up(storage: &dyn Storage) -> anyhow::Result<Vec<i64>> {
+    let applied = storage.applied_migrations().await?;
+    let current = applied.last().map(|m| m.version).unwrap_or(0);
+
+    if current > latest_version(storage) {
+        return Err(anyhow::anyhow!(
+            "数据库schema版本 {} 高于程序支持的版本 {}，请升级程序",
+            current,
+            latest_version(storage)
+        ));
+    }
+
+    if applied.is_empty() {
+        storage.prepare_legacy_schema().await?;
+    }
+
+    let mut newly_applied = Vec::new();
+    for migration in storage.migrations() {
+        if applied.iter().any(|m| m.version == migration.version) {
+            continue;
+        }
+
+        storage
+            .apply_migration(migration)
+            .await
+            .map_err(|e| anyhow::anyhow!("迁移 {} 执行失败: {}", migration.name, e))?;
+
+        info!("已应用迁移: {}", migration.name);
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+// 回滚最近应用的若干个迁移，返回本次回滚的版本号
+pub async fn migrate_down(storage: &dyn Storage, steps: usize) -> anyhow::Result<Vec<i64>> {
+    let applied = storage.applied_migrations().await?;
+
+    let mut reverted = Vec::new();
+    for applied_migration in applied.iter().rev().take(steps) {
+        let migration = storage
+            .migrations()
+            .iter()
+            .find(|m| m.version == applied_migration.version)
+            .ok_or_else(|| anyhow::anyhow!(
+                "无法回滚未知的迁移: {} ({})",
+                applied_migration.name,
+                applied_migration.version
+            ))?;
+
+        storage
+            .revert_migration(migration)
+            .await
+            .map_err(|e| anyhow::anyhow!("回滚迁移 {} 失败: {}", migration.name, e))?;
+
+        info!("已回滚迁移: {}", migration.name);
+        reverted.push(migration.version);
+    }
+
+    Ok(reverted)
+}
+
+// 汇总所有迁移的状态
+pub async fn migration_status(storage: &dyn Storage) -> anyhow::Result<Vec<MigrationStatus>> {
+    let applied = storage.applied_migrations().await?;
+
+    let mut statuses: Vec<MigrationStatus> = storage
+        .migrations()
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied_at: applied
+                .iter()
+                .find(|m| m.version == migration.version)
+                .map(|m| m.applied_at),
+            unknown: false,
+        })
+        .collect();
+
+    for applied_migration in applied {
+        if !storage.migrations().iter().any(|m| m.version == applied_migration.version) {
+            statuses.push(MigrationStatus {
+                version: applied_migration.version,
+                name: applied_migration.name,
+                applied_at: Some(applied_migration.applied_at),
+                unknown: true,
+            });
+        }
+    }
+
+    statuses.sort_by_key(|status| status.version);
+    Ok(statuses)
+}
+
+// 命令行输出迁移状态（demeter migrate status / demeter-admin db status）
+pub async fn print_status(storage: &dyn Storage) -> anyhow::Result<()> {
+    let statuses = migration_status(storage).await?;
+    println!("{}", t_args("cli.migrate_status", &[
+        ("current", &current_version(storage).await?),
+        ("latest", &latest_version(storage)),
+    ]));
+    for status in statuses {
+        let state = match (status.unknown, status.applied_at) {
+            (true, _) => t("cli.migration_unknown"),
+            (false, Some(applied_at)) => t_args(
+                "cli.migration_applied_at",
+                &[("time", &applied_at.format("%Y-%m-%d %H:%M:%S"))],
+            ),
+            (false, None) => t("cli.migration_pending"),
+        };
+        println!("{:>4}  {:<32} {}", status.version, status.name, state);
+    }
+    Ok(())
+}
+
+// 命令行输出应用迁移的结果
+pub fn print_applied(applied: &[i64]) {
+    if applied.is_empty() {
+        println!("{}", t("cli.migrate_up_to_date"));
+    } else {
+        println!("{}", t_args("cli.migrate_applied", &[
+            ("count", &applied.len()),
+            ("versions", &format!("{:?}", applied)),
+        ]));
+    }
+}
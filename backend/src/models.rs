@@ -1,18 +1,33 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+use crate::{
+    error::ErrorCode,
+    i18n,
+    repository::{ScoredTodo, TodoFilter},
+    search::{self, SearchTerms},
+    validation::{
+        empty_as_none, parse_date, parse_timestamp, trim, trim_optional, FieldError, Validate, Validator, PAGE_DEFAULT_LIMIT,
+        PAGE_MAX_LIMIT, SEARCH_DEFAULT_LIMIT, SEARCH_MAX_LIMIT, TITLE_MAX_CHARS,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: i64,
     pub username: String,
     pub password_hash: String,
     pub public_access: bool,
     pub readonly: bool,
+    // 由演示模式创建的账户
+    pub demo: bool,
+    // 界面语言偏好，如 zh-CN、en
+    pub locale: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Todo {
     pub id: i64,
     pub user_id: i64,
@@ -20,75 +35,190 @@ pub struct Todo {
     pub description: Option<String>,
     pub emoji: String,
     pub completed: bool,
+    // 截止日期，不含时间
+    pub due_date: Option<NaiveDate>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // 移到回收站的时间，未删除时为None
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+// 请求和响应类型同时实现序列化和反序列化，命令行客户端直接复用
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+// 首次设置：创建第一个用户，只在还没有任何用户时可用
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetupRequest {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub public_access: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetupStatusResponse {
+    pub setup_required: bool,
+    // 演示模式下的演示账户，前端在登录页展示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub demo: Option<DemoAccount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoAccount {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub user: UserResponse,
     pub token: String,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: i64,
     pub username: String,
     pub public_access: bool,
     pub readonly: bool,
+    pub locale: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateUserSettingsRequest {
-    pub public_access: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_access: Option<bool>,
+    // 空字符串表示清除语言偏好
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PublicAccessResponse {
     pub public_access: bool,
     pub username: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTodoRequest {
     pub title: String,
     pub description: Option<String>,
     pub emoji: String,
+    // YYYY-MM-DD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+// 未提供的字段保持不变，序列化时省略
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdateTodoRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emoji: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub completed: Option<bool>,
+    // 空字符串表示清除截止日期
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+// GET /api/todos 的查询参数，所有条件都是可选的
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TodoListQuery {
+    // 上一页响应中的 next_cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, deserialize_with = "empty_as_none", skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    // RFC 3339时间或 YYYY-MM-DD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    // 在标题和描述中搜索
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+}
+
+// 分页列表响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub meta: PageMeta,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PageMeta {
+    // 符合筛选条件的总数（不受分页影响）
+    pub total: i64,
+    pub limit: u32,
+    // 没有下一页时为null
+    pub next_cursor: Option<String>,
+}
+
+// GET /api/search 的查询参数
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SearchQuery {
+    // 空格分隔的多个词需要同时匹配
+    #[serde(default)]
+    pub q: String,
+    #[serde(default, deserialize_with = "empty_as_none", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+// 搜索结果：title和snippet中的匹配部分用<mark>标记，其余文字已做HTML转义
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub todo: TodoResponse,
+    // 相关度，越高越相关，只在同一次搜索的结果之间可比较
+    pub score: f64,
+    pub title: String,
+    // 描述中第一个匹配附近的片段，没有描述时为null
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoResponse {
     pub id: i64,
     pub title: String,
     pub description: Option<String>,
     pub emoji: String,
     pub completed: bool,
+    pub due_date: Option<NaiveDate>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // 只有回收站中的任务才有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize)]
+// 回收站中的任务在删除时间（deleted_at）的retention_days天后被永久删除
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashResponse {
+    pub data: Vec<TodoResponse>,
+    // 0表示永久保留
+    pub retention_days: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HistoryDay {
     pub date: String,
     pub count: i64,
@@ -96,11 +226,227 @@ pub struct HistoryDay {
     pub tasks: Vec<TodoResponse>,
 }
 
+// 探针响应
 #[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub storage: &'static str,
+    pub schema_version: i64,
+    pub latest_schema_version: i64,
+    pub features: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub message: String,
+    // 失败时的错误码，供客户端判断错误类型
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    // 参数校验失败时每个字段的错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+}
+
+impl Todo {
+    // 将部分更新请求合并到任务上，未提供的字段保持不变
+    pub fn apply_update(&mut self, update: UpdateTodoRequest, now: DateTime<Utc>) {
+        if let Some(title) = update.title {
+            self.title = title;
+        }
+        // 传入空字符串表示清除描述
+        if let Some(description) = update.description {
+            self.description = Some(description).filter(|description| !description.is_empty());
+        }
+        if let Some(emoji) = update.emoji {
+            self.emoji = emoji;
+        }
+        if let Some(completed) = update.completed {
+            self.completed = completed;
+        }
+        // 请求已通过校验，空字符串表示清除截止日期
+        if let Some(due_date) = update.due_date {
+            self.due_date = parse_date(&due_date);
+        }
+        self.updated_at = now;
+    }
+}
+
+// 用户名去掉首尾空白；密码保持原样，空白也是密码的一部分
+impl Validate for LoginRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim(&mut self.username);
+        let mut validator = Validator::new();
+        validator.required("username", &self.username);
+        validator.required("password", &self.password);
+        validator.finish()
+    }
+}
+
+impl Validate for RegisterRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim(&mut self.username);
+        let mut validator = Validator::new();
+        validator.username("username", &self.username);
+        validator.password("password", &self.password);
+        validator.finish()
+    }
+}
+
+impl Validate for SetupRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim(&mut self.username);
+        let mut validator = Validator::new();
+        validator.username("username", &self.username);
+        validator.password("password", &self.password);
+        validator.finish()
+    }
+}
+
+impl Validate for UpdateUserSettingsRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        let mut validator = Validator::new();
+        if let Some(locale) = &mut self.locale {
+            trim(locale);
+            if !locale.is_empty() {
+                if let Some(parsed) = validator.locale("locale", locale) {
+                    *locale = parsed.tag().to_string();
+                }
+            }
+        }
+        validator.finish()
+    }
+}
+
+impl Validate for TodoListQuery {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim_optional(&mut self.cursor);
+        trim_optional(&mut self.created_after);
+        trim_optional(&mut self.updated_since);
+        trim_optional(&mut self.emoji);
+        trim_optional(&mut self.q);
+
+        let mut validator = Validator::new();
+        if let Some(cursor) = &self.cursor {
+            validator.cursor("cursor", cursor);
+        }
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit, 1, PAGE_MAX_LIMIT);
+        }
+        if let Some(created_after) = &self.created_after {
+            validator.timestamp("created_after", created_after);
+        }
+        if let Some(updated_since) = &self.updated_since {
+            validator.timestamp("updated_since", updated_since);
+        }
+        if let Some(emoji) = &self.emoji {
+            validator.emoji("emoji", emoji);
+        }
+        if let Some(q) = &self.q {
+            validator.length("q", q, 1, TITLE_MAX_CHARS);
+        }
+        validator.finish()
+    }
+}
+
+impl TodoListQuery {
+    // 校验通过后转换为仓库的筛选条件
+    pub fn filter(&self) -> TodoFilter {
+        TodoFilter {
+            completed: self.completed,
+            created_after: self.created_after.as_deref().and_then(parse_timestamp),
+            updated_since: self.updated_since.as_deref().and_then(parse_timestamp),
+            emoji: self.emoji.clone(),
+            search: self.q.clone(),
+        }
+    }
+
+    pub fn page_limit(&self) -> u32 {
+        self.limit.unwrap_or(PAGE_DEFAULT_LIMIT)
+    }
+}
+
+impl Validate for SearchQuery {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim(&mut self.q);
+
+        let mut validator = Validator::new();
+        validator.length("q", &self.q, 0, TITLE_MAX_CHARS);
+        // 规范化为实际使用的搜索词，只有引号或通配符时视为未填写
+        self.q = SearchTerms::parse(&self.q).to_string();
+        validator.required("q", &self.q);
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit, 1, SEARCH_MAX_LIMIT);
+        }
+        validator.finish()
+    }
+}
+
+impl SearchQuery {
+    pub fn terms(&self) -> SearchTerms {
+        SearchTerms::parse(&self.q)
+    }
+
+    pub fn page_limit(&self) -> u32 {
+        self.limit.unwrap_or(SEARCH_DEFAULT_LIMIT)
+    }
+}
+
+impl Validate for CreateTodoRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        trim(&mut self.title);
+        trim_optional(&mut self.description);
+        trim(&mut self.emoji);
+        trim_optional(&mut self.due_date);
+
+        let mut validator = Validator::new();
+        validator.title("title", &self.title);
+        if let Some(description) = &self.description {
+            validator.description("description", description);
+        }
+        validator.emoji("emoji", &self.emoji);
+        if let Some(due_date) = &self.due_date {
+            validator.date("due_date", due_date);
+        }
+        validator.finish()
+    }
+}
+
+// 只校验提供了的字段
+impl Validate for UpdateTodoRequest {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>> {
+        let mut validator = Validator::new();
+        if let Some(title) = &mut self.title {
+            trim(title);
+            validator.title("title", title);
+        }
+        if let Some(description) = &mut self.description {
+            trim(description);
+            validator.description("description", description);
+        }
+        if let Some(emoji) = &mut self.emoji {
+            trim(emoji);
+            validator.emoji("emoji", emoji);
+        }
+        if let Some(due_date) = &mut self.due_date {
+            trim(due_date);
+            if !due_date.is_empty() {
+                validator.date("due_date", due_date);
+            }
+        }
+        validator.finish()
+    }
 }
 
 impl<T> ApiResponse<T> {
@@ -108,15 +454,19 @@ impl<T> ApiResponse<T> {
         Self {
             success: true,
             data: Some(data),
-            message: "操作成功".to_string(),
+            message: i18n::t("api.success"),
+            code: None,
+            errors: None,
         }
     }
 
-    pub fn error(message: &str) -> Self {
+    pub fn error(code: ErrorCode, message: String) -> Self {
         Self {
             success: false,
             data: None,
-            message: message.to_string(),
+            message,
+            code: Some(code),
+            errors: None,
         }
     }
 }
@@ -129,8 +479,21 @@ impl From<Todo> for TodoResponse {
             description: todo.description,
             emoji: todo.emoji,
             completed: todo.completed,
+            due_date: todo.due_date,
             created_at: todo.created_at,
             updated_at: todo.updated_at,
+            deleted_at: todo.deleted_at,
+        }
+    }
+}
+
+impl SearchHit {
+    pub fn new(hit: ScoredTodo, terms: &SearchTerms) -> Self {
+        Self {
+            title: search::highlight(&hit.todo.title, terms),
+            snippet: hit.todo.description.as_deref().map(|description| search::snippet(description, terms)),
+            score: hit.score,
+            todo: hit.todo.into(),
         }
     }
 }
@@ -142,6 +505,7 @@ impl From<User> for UserResponse {
             username: user.username,
             public_access: user.public_access,
             readonly: user.readonly,
+            locale: user.locale,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+
+use crate::{
+    models::{Todo, User},
+    search::SearchTerms,
+};
+
+// 新建用户所需字段
+#[derive(Debug)]
+pub struct NewUser<'a> {
+    pub username: &'a str,
+    pub password_hash: &'a str,
+    pub public_access: bool,
+    pub readonly: bool,
+    pub demo: bool,
+}
+
+// 新建任务所需字段
+#[derive(Debug)]
+pub struct NewTodo<'a> {
+    pub user_id: i64,
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub emoji: &'a str,
+    pub due_date: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+}
+
+// 导入任务所需字段，可以指定完成状态和更新时间
+#[derive(Debug)]
+pub struct ImportedTodo<'a> {
+    pub user_id: i64,
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub emoji: &'a str,
+    pub due_date: Option<NaiveDate>,
+    pub completed: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// 任务列表的筛选条件，未设置的条件不生效
+#[derive(Debug, Default, Clone)]
+pub struct TodoFilter {
+    pub completed: Option<bool>,
+    // 创建时间晚于该时间（不含）
+    pub created_after: Option<DateTime<Utc>>,
+    // 更新时间不早于该时间（含），用于增量同步
+    pub updated_since: Option<DateTime<Utc>>,
+    pub emoji: Option<String>,
+    // 标题或描述包含该文字，不区分大小写
+    pub search: Option<String>,
+}
+
+// 分页游标：上一页最后一个任务的位置，列表按 (created_at, id) 倒序
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: i64,
+}
+
+impl TodoCursor {
+    pub fn after(todo: &Todo) -> Self {
+        Self {
+            created_at: todo.created_at,
+            id: todo.id,
+        }
+    }
+
+    // 对客户端不透明的字符串
+    pub fn encode(&self) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = general_purpose::URL_SAFE_NO_PAD.decode(value).ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (created_at, id) = text.split_once('|')?;
+        Some(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).ok()?.with_timezone(&Utc),
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+// 带相关度分数的搜索结果，分数越高越相关，只在同一次搜索的结果之间可比较
+#[derive(Debug)]
+pub struct ScoredTodo {
+    pub todo: Todo,
+    pub score: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    // 按相关度从高到低排列
+    pub hits: Vec<ScoredTodo>,
+    // 匹配的任务总数（不受数量限制影响）
+    pub total: i64,
+}
+
+// 按日期汇总的任务数量（历史热力图）
+#[derive(Debug)]
+pub struct HistoryBucket {
+    pub date: String,
+    pub count: i64,
+    pub completed_count: i64,
+}
+
+// 全部用户的任务数量统计（监控指标）
+#[derive(Debug)]
+pub struct TodoTotals {
+    pub open: i64,
+    pub completed: i64,
+}
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn list(&self) -> anyhow::Result<Vec<User>>;
+    async fn find_by_id(&self, id: i64) -> anyhow::Result<Option<User>>;
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>>;
+    // 按id排序的第一个用户
+    async fn first(&self) -> anyhow::Result<Option<User>>;
+    // 按id排序的第一个公开访问用户
+    async fn first_public(&self) -> anyhow::Result<Option<User>>;
+    async fn insert(&self, user: NewUser<'_>) -> anyhow::Result<User>;
+    async fn set_public_access(&self, id: i64, public_access: bool) -> anyhow::Result<()>;
+    async fn set_locale(&self, id: i64, locale: Option<&str>) -> anyhow::Result<()>;
+    async fn set_readonly(&self, id: i64, readonly: bool) -> anyhow::Result<()>;
+    async fn set_password_hash(&self, id: i64, password_hash: &str) -> anyhow::Result<()>;
+    // 同时删除该用户的所有任务，用户不存在时返回false
+    async fn delete(&self, id: i64) -> anyhow::Result<bool>;
+}
+
+// 所有任务操作都限定在所属用户范围内；除回收站相关的方法外，都不包括回收站中的任务
+#[async_trait]
+pub trait TodoRepository: Send + Sync {
+    async fn find_by_id(&self, id: i64, user_id: i64) -> anyhow::Result<Option<Todo>>;
+    // 按创建时间倒序
+    async fn list_for_user(&self, user_id: i64) -> anyhow::Result<Vec<Todo>>;
+    // 符合条件的任务，按创建时间倒序，从游标之后开始最多返回limit个
+    async fn list_page(
+        &self,
+        user_id: i64,
+        filter: &TodoFilter,
+        after: Option<&TodoCursor>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Todo>>;
+    async fn count(&self, user_id: i64, filter: &TodoFilter) -> anyhow::Result<i64>;
+    // 在标题和描述中搜索，按相关度排序最多返回limit个；terms不能为空
+    async fn search(&self, user_id: i64, terms: &SearchTerms, limit: i64) -> anyhow::Result<SearchResults>;
+    async fn insert(&self, todo: NewTodo<'_>) -> anyhow::Result<Todo>;
+    // 在同一个事务中写入，任何一个失败时都不写入，返回写入的数量
+    async fn import(&self, todos: &[ImportedTodo<'_>]) -> anyhow::Result<u64>;
+    async fn update(&self, todo: &Todo) -> anyhow::Result<()>;
+    // 永久删除，回收站中的任务也会被删除
+    async fn delete(&self, id: i64, user_id: i64) -> anyhow::Result<bool>;
+    // 移到回收站，任务不存在或已在回收站中时返回false
+    async fn trash(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<bool>;
+    // 从回收站恢复并更新updated_at，任务不在回收站中时返回None
+    async fn restore(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<Option<Todo>>;
+    // 回收站中的任务，按删除时间倒序
+    async fn list_trash(&self, user_id: i64) -> anyhow::Result<Vec<Todo>>;
+    // 永久删除所有用户在该时间之前移到回收站的任务，返回删除的数量
+    async fn purge_trashed(&self, before: DateTime<Utc>) -> anyhow::Result<u64>;
+    // 最近若干天内按UTC日期分组的任务数量，按日期升序
+    async fn history_buckets(&self, user_id: i64, days: i64) -> anyhow::Result<Vec<HistoryBucket>>;
+    // 指定UTC日期（YYYY-MM-DD）创建的任务，按创建时间升序
+    async fn list_created_on(&self, user_id: i64, date: &str) -> anyhow::Result<Vec<Todo>>;
+    async fn totals(&self) -> anyhow::Result<TodoTotals>;
+    // 每个用户不在回收站中的任务数量，没有任务的用户不在结果中
+    async fn count_by_user(&self) -> anyhow::Result<HashMap<i64, i64>>;
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::{
+    models::Todo,
+    repository::{ScoredTodo, SearchResults},
+};
+
+// SQLite的trigram分词器按3个字符的片段建索引，更短的搜索词无法使用全文索引
+pub const INDEXED_MIN_CHARS: usize = 3;
+// 一次搜索最多使用的搜索词，多余的忽略
+pub const MAX_TERMS: usize = 8;
+// 标题中的匹配比描述中的更重要（同时用作bm25的列权重）
+pub const TITLE_WEIGHT: f64 = 10.0;
+
+// 高亮标记，标记以外的文字已做HTML转义
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+// 描述摘要的长度，以及摘要中第一个匹配之前保留的字符数
+const SNIPPET_CHARS: usize = 80;
+const SNIPPET_LEAD_CHARS: usize = 20;
+
+// 解析后的搜索词：按空白拆分，不区分大小写去重，多个词之间是"并且"关系
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchTerms {
+    terms: Vec<String>,
+}
+
+impl SearchTerms {
+    // 去掉引号和末尾的通配符*：搜索词本来就按子串匹配，前缀自然也能匹配
+    pub fn parse(query: &str) -> Self {
+        let mut terms: Vec<String> = Vec::new();
+        for word in query.split_whitespace() {
+            let term: String = word.chars().filter(|&c| c != '"').collect();
+            let term = term.trim_end_matches('*');
+            if term.is_empty() || terms.iter().any(|existing| fold(existing) == fold(term)) {
+                continue;
+            }
+            terms.push(term.to_string());
+            if terms.len() == MAX_TERMS {
+                break;
+            }
+        }
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    // 可以用全文索引匹配的搜索词
+    pub fn indexed(&self) -> impl Iterator<Item = &str> {
+        self.terms.iter().map(String::as_str).filter(|term| is_indexed(term))
+    }
+
+    // 太短、需要逐行匹配的搜索词
+    pub fn unindexed(&self) -> impl Iterator<Item = &str> {
+        self.terms.iter().map(String::as_str).filter(|term| !is_indexed(term))
+    }
+
+    // FTS5的MATCH表达式：每个词作为一个短语，空格分隔表示AND；没有可索引的词时返回None
+    pub fn fts_query(&self) -> Option<String> {
+        let phrases: Vec<String> = self
+            .indexed()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect();
+        if phrases.is_empty() {
+            None
+        } else {
+            Some(phrases.join(" "))
+        }
+    }
+
+    // 每个词都出现在标题或描述中
+    pub fn matches(&self, todo: &Todo) -> bool {
+        let title = fold(&todo.title);
+        let description = todo.description.as_deref().map(fold).unwrap_or_default();
+        self.terms.iter().all(|term| {
+            let term = fold(term);
+            !find_all(&title, &term).is_empty() || !find_all(&description, &term).is_empty()
+        })
+    }
+
+    // 没有全文索引时的相关度：按匹配次数计分，标题中的匹配权重更高
+    pub fn score(&self, todo: &Todo) -> f64 {
+        let title = fold(&todo.title);
+        let description = todo.description.as_deref().map(fold).unwrap_or_default();
+        self.terms
+            .iter()
+            .map(|term| {
+                let term = fold(term);
+                find_all(&title, &term).len() as f64 * TITLE_WEIGHT + find_all(&description, &term).len() as f64
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for SearchTerms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.terms.join(" "))
+    }
+}
+
+fn is_indexed(term: &str) -> bool {
+    term.chars().count() >= INDEXED_MIN_CHARS
+}
+
+// 在内存中筛选并排序候选任务（没有全文索引的存储后端，以及搜索词都太短时使用）
+pub fn rank(todos: Vec<Todo>, terms: &SearchTerms, limit: i64) -> SearchResults {
+    let mut hits: Vec<ScoredTodo> = todos
+        .into_iter()
+        .filter(|todo| terms.matches(todo))
+        .map(|todo| ScoredTodo {
+            score: terms.score(&todo),
+            todo,
+        })
+        .collect();
+    // 分数相同时较新的任务排在前面
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| (b.todo.created_at, b.todo.id).cmp(&(a.todo.created_at, a.todo.id)))
+    });
+    let total = hits.len() as i64;
+    hits.truncate(usize::try_from(limit).unwrap_or(0));
+    SearchResults { hits, total }
+}
+
+// 高亮文字中所有搜索词的出现位置
+pub fn highlight(text: &str, terms: &SearchTerms) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let ranges = match_ranges(&chars, terms);
+    render(&chars, &ranges, 0, chars.len())
+}
+
+// 截取第一个匹配附近的一段文字并高亮；没有匹配时取开头一段
+pub fn snippet(text: &str, terms: &SearchTerms) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let ranges = match_ranges(&chars, terms);
+    let start = match ranges.first() {
+        Some(&(first, _)) if chars.len() > SNIPPET_CHARS => {
+            first.saturating_sub(SNIPPET_LEAD_CHARS).min(chars.len() - SNIPPET_CHARS)
+        }
+        _ => 0,
+    };
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&render(&chars, &ranges, start, end));
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+// 不区分大小写匹配前的转换：逐字符转小写，只取第一个字符以保持字符位置不变。
+// 所有存储后端的筛选和搜索都使用这一规则（SQLite中注册为SQL函数casefold）
+pub fn fold_case(text: &str) -> String {
+    text.chars().map(fold_char).collect()
+}
+
+// 任务列表的q筛选：标题或描述中包含该文字，不区分大小写
+pub fn todo_contains(todo: &Todo, search: &str) -> bool {
+    let search = fold_case(search);
+    let contains = |text: &str| fold_case(text).contains(&search);
+    contains(&todo.title) || todo.description.as_deref().is_some_and(contains)
+}
+
+fn fold_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn fold(text: &str) -> Vec<char> {
+    text.chars().map(fold_char).collect()
+}
+
+// needle在haystack中不重叠的出现位置，按字符计算的 [start, end)
+fn find_all(haystack: &[char], needle: &[char]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return ranges;
+    }
+    let mut i = 0;
+    while i + needle.len() <= haystack.len() {
+        if haystack[i..i + needle.len()] == *needle {
+            ranges.push((i, i + needle.len()));
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+// 所有搜索词的匹配位置，按起点排序并合并重叠部分
+fn match_ranges(chars: &[char], terms: &SearchTerms) -> Vec<(usize, usize)> {
+    let folded: Vec<char> = chars.iter().copied().map(fold_char).collect();
+    let mut ranges: Vec<(usize, usize)> = terms.terms.iter().flat_map(|term| find_all(&folded, &fold(term))).collect();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// 输出 [start, end) 范围内的文字，匹配部分加高亮标记，其余部分做HTML转义
+fn render(chars: &[char], ranges: &[(usize, usize)], start: usize, end: usize) -> String {
+    let mut output = String::new();
+    let mut position = start;
+    for &(range_start, range_end) in ranges {
+        let (range_start, range_end) = (range_start.max(start), range_end.min(end));
+        if range_start >= range_end {
+            continue;
+        }
+        escape_into(&mut output, &chars[position..range_start]);
+        output.push_str(HIGHLIGHT_START);
+        escape_into(&mut output, &chars[range_start..range_end]);
+        output.push_str(HIGHLIGHT_END);
+        position = range_end;
+    }
+    escape_into(&mut output, &chars[position..end]);
+    output
+}
+
+fn escape_into(output: &mut String, chars: &[char]) {
+    for &c in chars {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            _ => output.push(c),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use axum::Router;
+use axum_server::Handle;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{config::ServerConfig, tls};
+
+// 监听TCP（可选TLS）或Unix socket，收到SIGTERM/SIGINT后停止接受新连接，
+// 等待进行中的请求完成，超过shutdown_timeout_secs后强制断开
+pub async fn run(config: &ServerConfig, app: Router) -> anyhow::Result<()> {
+    let timeout = Duration::from_secs(config.shutdown_timeout_secs);
+
+    if !config.unix_socket.is_empty() {
+        if config.tls.enabled {
+            return Err(anyhow::anyhow!("Unix socket监听不支持TLS，请关闭 server.tls.enabled"));
+        }
+        return serve_unix(config, app, timeout).await;
+    }
+
+    let address = tokio::net::lookup_host(config.address())
+        .await?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("无法解析监听地址 {}", config.address()))?;
+
+    let handle = Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown_signal(timeout).await;
+            handle.graceful_shutdown(Some(timeout));
+        }
+    });
+
+    if config.tls.enabled {
+        let rustls = tls::load(&config.tls).await?;
+        tls::spawn_reload(rustls.clone(), config.tls.clone());
+        if config.tls.redirect_http_port != 0 {
+            tls::spawn_redirect(config.host.clone(), config.tls.redirect_http_port, config.port);
+        }
+
+        info!("服务器启动在 https://{}", address);
+        axum_server::bind_rustls(address, rustls)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await?;
+    } else {
+        info!("服务器启动在 http://{}", address);
+        axum_server::bind(address)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn shutdown_signal(timeout: Duration) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("无法监听Ctrl+C信号: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("无法监听SIGTERM信号: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("收到停止信号，等待进行中的请求完成（最多 {} 秒）", timeout.as_secs());
+}
+
+#[cfg(unix)]
+async fn serve_unix(config: &ServerConfig, app: Router, timeout: Duration) -> anyhow::Result<()> {
+    use hyper_util::{
+        rt::{TokioExecutor, TokioIo},
+        server::{conn::auto::Builder, graceful::GracefulShutdown},
+        service::TowerToHyperService,
+    };
+    use std::{
+        fs,
+        io::ErrorKind,
+        os::unix::{
+            fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+            net::UnixStream,
+        },
+        path::Path,
+    };
+    use tokio::net::UnixListener;
+    use tracing::error;
+
+    let path = Path::new(&config.unix_socket);
+    let mode = u32::from_str_radix(&config.unix_socket_mode, 8)
+        .map_err(|_| anyhow::anyhow!("server.unix_socket_mode 必须是八进制权限，例如 \"660\""))?;
+
+    // 上次异常退出可能遗留socket文件，只删除没有进程监听的socket，避免误删普通文件或其他实例的socket
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(anyhow::anyhow!("{} 已存在且不是socket文件", path.display()));
+        }
+        match UnixStream::connect(path) {
+            Ok(_) => return Err(anyhow::anyhow!("{} 已有其他进程在监听", path.display())),
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => fs::remove_file(path)?,
+            Err(e) => return Err(anyhow::anyhow!("无法检查Unix socket {}: {}", path.display(), e)),
+        }
+    }
+
+    // 先在只有当前用户可以访问的临时目录中创建socket并设置权限，再移动到目标路径，
+    // 避免socket在设置权限之前以umask决定的权限出现在目标路径上
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("server.unix_socket 不是有效的文件路径: {}", path.display()))?;
+    let staging = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|e| anyhow::anyhow!("无法创建临时目录 {}: {}", staging.display(), e))?;
+    let staged = staging.join("socket");
+    let listener = UnixListener::bind(&staged)
+        .map_err(|e| anyhow::anyhow!("无法监听Unix socket {}: {}", path.display(), e))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(mode))?;
+            fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+    let _ = fs::remove_file(&staged);
+    let _ = fs::remove_dir(&staging);
+    let listener = listener?;
+    info!("服务器监听Unix socket {} (权限 {:o})", path.display(), mode);
+
+    let service = TowerToHyperService::new(app);
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    let shutdown = shutdown_signal(timeout);
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        error!("接受Unix socket连接失败: {}", e);
+                        continue;
+                    }
+                };
+                let connection = builder
+                    .serve_connection_with_upgrades(TokioIo::new(stream), service.clone())
+                    .into_owned();
+                let connection = graceful.watch(connection);
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        warn!("Unix socket连接异常: {}", e);
+                    }
+                });
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+
+    drop(listener);
+    if tokio::time::timeout(timeout, graceful.shutdown()).await.is_err() {
+        warn!("等待请求完成超时，强制断开剩余连接");
+    }
+    if let Err(e) = fs::remove_file(path) {
+        warn!("删除Unix socket文件 {} 失败: {}", path.display(), e);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn serve_unix(_config: &ServerConfig, _app: Router, _timeout: Duration) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("当前平台不支持Unix socket监听"))
+}
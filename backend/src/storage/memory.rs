@@ -0,0 +1,424 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::Storage;
+use crate::{
+    migrations::{AppliedMigration, Migration},
+    models::{Todo, User},
+    repository::{
+        HistoryBucket, ImportedTodo, NewTodo, NewUser, SearchResults, TodoCursor, TodoFilter, TodoRepository, TodoTotals,
+        UserRepository,
+    },
+    search::{self, SearchTerms},
+};
+
+#[derive(Default)]
+struct MemoryData {
+    users: Vec<User>,
+    todos: Vec<Todo>,
+    next_user_id: i64,
+    next_todo_id: i64,
+}
+
+type SharedData = Arc<Mutex<MemoryData>>;
+
+fn lock(data: &SharedData) -> MutexGuard<'_, MemoryData> {
+    // 锁中毒只可能来自其它线程panic，数据本身仍然可用
+    data.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// 纯内存存储，不需要schema迁移
+pub struct MemoryStorage {
+    users: MemoryUserRepository,
+    todos: MemoryTodoRepository,
+}
+
+pub struct MemoryUserRepository {
+    data: SharedData,
+}
+
+pub struct MemoryTodoRepository {
+    data: SharedData,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        let data = SharedData::default();
+        Self {
+            users: MemoryUserRepository { data: data.clone() },
+            todos: MemoryTodoRepository { data },
+        }
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    fn backend_name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn users(&self) -> &dyn UserRepository {
+        &self.users
+    }
+
+    fn todos(&self) -> &dyn TodoRepository {
+        &self.todos
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        &[]
+    }
+
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>> {
+        Ok(Vec::new())
+    }
+
+    async fn apply_migration(&self, _migration: &Migration) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn revert_migration(&self, _migration: &Migration) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn close(&self) {}
+}
+
+#[async_trait]
+impl UserRepository for MemoryUserRepository {
+    async fn list(&self) -> anyhow::Result<Vec<User>> {
+        Ok(lock(&self.data).users.clone())
+    }
+
+    async fn find_by_id(&self, id: i64) -> anyhow::Result<Option<User>> {
+        Ok(lock(&self.data).users.iter().find(|user| user.id == id).cloned())
+    }
+
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        Ok(lock(&self.data).users.iter().find(|user| user.username == username).cloned())
+    }
+
+    async fn first(&self) -> anyhow::Result<Option<User>> {
+        Ok(lock(&self.data).users.first().cloned())
+    }
+
+    async fn first_public(&self) -> anyhow::Result<Option<User>> {
+        Ok(lock(&self.data).users.iter().find(|user| user.public_access).cloned())
+    }
+
+    async fn insert(&self, user: NewUser<'_>) -> anyhow::Result<User> {
+        let mut data = lock(&self.data);
+        if data.users.iter().any(|existing| existing.username == user.username) {
+            return Err(anyhow::anyhow!("UNIQUE constraint failed: users.username"));
+        }
+
+        data.next_user_id += 1;
+        let user = User {
+            id: data.next_user_id,
+            username: user.username.to_string(),
+            password_hash: user.password_hash.to_string(),
+            public_access: user.public_access,
+            readonly: user.readonly,
+            demo: user.demo,
+            locale: None,
+            created_at: Utc::now(),
+        };
+        data.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn set_public_access(&self, id: i64, public_access: bool) -> anyhow::Result<()> {
+        if let Some(user) = lock(&self.data).users.iter_mut().find(|user| user.id == id) {
+            user.public_access = public_access;
+        }
+        Ok(())
+    }
+
+    async fn set_locale(&self, id: i64, locale: Option<&str>) -> anyhow::Result<()> {
+        if let Some(user) = lock(&self.data).users.iter_mut().find(|user| user.id == id) {
+            user.locale = locale.map(str::to_string);
+        }
+        Ok(())
+    }
+
+    async fn set_readonly(&self, id: i64, readonly: bool) -> anyhow::Result<()> {
+        if let Some(user) = lock(&self.data).users.iter_mut().find(|user| user.id == id) {
+            user.readonly = readonly;
+        }
+        Ok(())
+    }
+
+    async fn set_password_hash(&self, id: i64, password_hash: &str) -> anyhow::Result<()> {
+        if let Some(user) = lock(&self.data).users.iter_mut().find(|user| user.id == id) {
+            user.password_hash = password_hash.to_string();
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> anyhow::Result<bool> {
+        let mut data = lock(&self.data);
+        let before = data.users.len();
+        data.users.retain(|user| user.id != id);
+        data.todos.retain(|todo| todo.user_id != id);
+        Ok(data.users.len() < before)
+    }
+}
+
+#[async_trait]
+impl TodoRepository for MemoryTodoRepository {
+    async fn find_by_id(&self, id: i64, user_id: i64) -> anyhow::Result<Option<Todo>> {
+        Ok(lock(&self.data)
+            .todos
+            .iter()
+            .find(|todo| todo.id == id && is_active(todo, user_id))
+            .cloned())
+    }
+
+    async fn list_for_user(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| is_active(todo, user_id))
+            .cloned()
+            .collect();
+        todos.sort_by_key(|todo| std::cmp::Reverse(todo.created_at));
+        Ok(todos)
+    }
+
+    async fn list_page(
+        &self,
+        user_id: i64,
+        filter: &TodoFilter,
+        after: Option<&TodoCursor>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| is_active(todo, user_id) && matches_filter(todo, filter))
+            .filter(|todo| after.is_none_or(|cursor| (todo.created_at, todo.id) < (cursor.created_at, cursor.id)))
+            .cloned()
+            .collect();
+        todos.sort_by_key(|todo| std::cmp::Reverse((todo.created_at, todo.id)));
+        todos.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(todos)
+    }
+
+    async fn count(&self, user_id: i64, filter: &TodoFilter) -> anyhow::Result<i64> {
+        let count = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| is_active(todo, user_id) && matches_filter(todo, filter))
+            .count();
+        Ok(count as i64)
+    }
+
+    async fn search(&self, user_id: i64, terms: &SearchTerms, limit: i64) -> anyhow::Result<SearchResults> {
+        let todos: Vec<Todo> = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| is_active(todo, user_id))
+            .cloned()
+            .collect();
+        Ok(search::rank(todos, terms, limit))
+    }
+
+    async fn insert(&self, todo: NewTodo<'_>) -> anyhow::Result<Todo> {
+        let mut data = lock(&self.data);
+        data.next_todo_id += 1;
+        let todo = Todo {
+            id: data.next_todo_id,
+            user_id: todo.user_id,
+            title: todo.title.to_string(),
+            description: todo.description.map(str::to_string),
+            emoji: todo.emoji.to_string(),
+            completed: false,
+            due_date: todo.due_date,
+            created_at: todo.created_at,
+            updated_at: todo.created_at,
+            deleted_at: None,
+        };
+        data.todos.push(todo.clone());
+        Ok(todo)
+    }
+
+    async fn import(&self, todos: &[ImportedTodo<'_>]) -> anyhow::Result<u64> {
+        let mut data = lock(&self.data);
+        for todo in todos {
+            data.next_todo_id += 1;
+            let todo = Todo {
+                id: data.next_todo_id,
+                user_id: todo.user_id,
+                title: todo.title.to_string(),
+                description: todo.description.map(str::to_string),
+                emoji: todo.emoji.to_string(),
+                completed: todo.completed,
+                due_date: todo.due_date,
+                created_at: todo.created_at,
+                updated_at: todo.updated_at,
+                deleted_at: None,
+            };
+            data.todos.push(todo);
+        }
+        Ok(todos.len() as u64)
+    }
+
+    async fn update(&self, todo: &Todo) -> anyhow::Result<()> {
+        let mut data = lock(&self.data);
+        if let Some(existing) = data
+            .todos
+            .iter_mut()
+            .find(|existing| existing.id == todo.id && is_active(existing, todo.user_id))
+        {
+            existing.title = todo.title.clone();
+            existing.description = todo.description.clone();
+            existing.emoji = todo.emoji.clone();
+            existing.completed = todo.completed;
+            existing.due_date = todo.due_date;
+            existing.updated_at = todo.updated_at;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> anyhow::Result<bool> {
+        let mut data = lock(&self.data);
+        let before = data.todos.len();
+        data.todos.retain(|todo| !(todo.id == id && todo.user_id == user_id));
+        Ok(data.todos.len() < before)
+    }
+
+    async fn trash(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        let mut data = lock(&self.data);
+        match data.todos.iter_mut().find(|todo| todo.id == id && is_active(todo, user_id)) {
+            Some(todo) => {
+                todo.deleted_at = Some(at);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn restore(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<Option<Todo>> {
+        let mut data = lock(&self.data);
+        let todo = data
+            .todos
+            .iter_mut()
+            .find(|todo| todo.id == id && todo.user_id == user_id && todo.deleted_at.is_some())
+            .map(|todo| {
+                todo.deleted_at = None;
+                todo.updated_at = at;
+                todo.clone()
+            });
+        Ok(todo)
+    }
+
+    async fn list_trash(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| todo.user_id == user_id && todo.deleted_at.is_some())
+            .cloned()
+            .collect();
+        todos.sort_by_key(|todo| std::cmp::Reverse((todo.deleted_at, todo.id)));
+        Ok(todos)
+    }
+
+    async fn purge_trashed(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let mut data = lock(&self.data);
+        let count = data.todos.len();
+        data.todos.retain(|todo| todo.deleted_at.is_none_or(|deleted_at| deleted_at >= before));
+        Ok((count - data.todos.len()) as u64)
+    }
+
+    async fn history_buckets(&self, user_id: i64, days: i64) -> anyhow::Result<Vec<HistoryBucket>> {
+        let since = (Utc::now() - Duration::days(days)).date_naive();
+
+        let mut buckets: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for todo in lock(&self.data).todos.iter() {
+            if !is_active(todo, user_id) || todo.created_at.date_naive() < since {
+                continue;
+            }
+            let bucket = buckets
+                .entry(todo.created_at.format("%Y-%m-%d").to_string())
+                .or_default();
+            bucket.0 += 1;
+            if todo.completed {
+                bucket.1 += 1;
+            }
+        }
+
+        Ok(buckets
+            .into_iter()
+            .map(|(date, (count, completed_count))| HistoryBucket {
+                date,
+                count,
+                completed_count,
+            })
+            .collect())
+    }
+
+    async fn list_created_on(&self, user_id: i64, date: &str) -> anyhow::Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = lock(&self.data)
+            .todos
+            .iter()
+            .filter(|todo| {
+                is_active(todo, user_id) && todo.created_at.format("%Y-%m-%d").to_string() == date
+            })
+            .cloned()
+            .collect();
+        todos.sort_by_key(|todo| todo.created_at);
+        Ok(todos)
+    }
+
+    async fn totals(&self) -> anyhow::Result<TodoTotals> {
+        let data = lock(&self.data);
+        let (completed, open): (Vec<&Todo>, Vec<&Todo>) = data
+            .todos
+            .iter()
+            .filter(|todo| todo.deleted_at.is_none())
+            .partition(|todo| todo.completed);
+        Ok(TodoTotals {
+            open: open.len() as i64,
+            completed: completed.len() as i64,
+        })
+    }
+
+    async fn count_by_user(&self) -> anyhow::Result<HashMap<i64, i64>> {
+        let mut counts = HashMap::new();
+        for todo in lock(&self.data).todos.iter().filter(|todo| todo.deleted_at.is_none()) {
+            *counts.entry(todo.user_id).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+}
+
+// 属于该用户且不在回收站中
+fn is_active(todo: &Todo, user_id: i64) -> bool {
+    todo.user_id == user_id && todo.deleted_at.is_none()
+}
+
+fn matches_filter(todo: &Todo, filter: &TodoFilter) -> bool {
+    filter.completed.is_none_or(|completed| todo.completed == completed)
+        && filter.created_after.is_none_or(|time| todo.created_at > time)
+        && filter.updated_since.is_none_or(|time| todo.updated_at >= time)
+        && filter.emoji.as_ref().is_none_or(|emoji| &todo.emoji == emoji)
+        && filter.search.as_ref().is_none_or(|search| search::todo_contains(todo, search))
+}
@@ -0,0 +1,234 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use metrics::{counter, histogram};
+use std::{collections::HashMap, future::Future, path::Path, sync::Arc, time::Instant};
+
+use super::{PoolStats, Storage};
+use crate::{
+    migrations::{AppliedMigration, Migration},
+    models::{Todo, User},
+    repository::{
+        HistoryBucket, ImportedTodo, NewTodo, NewUser, SearchResults, TodoCursor, TodoFilter, TodoRepository, TodoTotals,
+        UserRepository,
+    },
+    search::SearchTerms,
+};
+
+// 包装任意存储后端，记录每个仓库操作的耗时和错误次数
+pub struct MeteredStorage {
+    inner: Arc<dyn Storage>,
+    users: MeteredUserRepository,
+    todos: MeteredTodoRepository,
+}
+
+pub struct MeteredUserRepository {
+    inner: Arc<dyn Storage>,
+}
+
+pub struct MeteredTodoRepository {
+    inner: Arc<dyn Storage>,
+}
+
+impl MeteredStorage {
+    pub fn new(inner: Arc<dyn Storage>) -> Self {
+        Self {
+            users: MeteredUserRepository { inner: inner.clone() },
+            todos: MeteredTodoRepository { inner: inner.clone() },
+            inner,
+        }
+    }
+}
+
+async fn timed<T>(operation: &'static str, future: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    let start = Instant::now();
+    let result = future.await;
+    histogram!("demeter_db_query_duration_seconds", "operation" => operation)
+        .record(start.elapsed().as_secs_f64());
+    if result.is_err() {
+        counter!("demeter_db_query_errors_total", "operation" => operation).increment(1);
+    }
+    result
+}
+
+#[async_trait]
+impl Storage for MeteredStorage {
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    fn users(&self) -> &dyn UserRepository {
+        &self.users
+    }
+
+    fn todos(&self) -> &dyn TodoRepository {
+        &self.todos
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        self.inner.migrations()
+    }
+
+    async fn prepare_legacy_schema(&self) -> anyhow::Result<()> {
+        self.inner.prepare_legacy_schema().await
+    }
+
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>> {
+        self.inner.applied_migrations().await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        self.inner.apply_migration(migration).await
+    }
+
+    async fn revert_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        self.inner.revert_migration(migration).await
+    }
+
+    async fn backup(&self, destination: &Path) -> anyhow::Result<()> {
+        self.inner.backup(destination).await
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.inner.ping().await
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        self.inner.check().await
+    }
+
+    async fn vacuum(&self) -> anyhow::Result<()> {
+        self.inner.vacuum().await
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.inner.pool_stats()
+    }
+
+    async fn close(&self) {
+        self.inner.close().await
+    }
+}
+
+#[async_trait]
+impl UserRepository for MeteredUserRepository {
+    async fn list(&self) -> anyhow::Result<Vec<User>> {
+        timed("users.list", self.inner.users().list()).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> anyhow::Result<Option<User>> {
+        timed("users.find_by_id", self.inner.users().find_by_id(id)).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        timed("users.find_by_username", self.inner.users().find_by_username(username)).await
+    }
+
+    async fn first(&self) -> anyhow::Result<Option<User>> {
+        timed("users.first", self.inner.users().first()).await
+    }
+
+    async fn first_public(&self) -> anyhow::Result<Option<User>> {
+        timed("users.first_public", self.inner.users().first_public()).await
+    }
+
+    async fn insert(&self, user: NewUser<'_>) -> anyhow::Result<User> {
+        timed("users.insert", self.inner.users().insert(user)).await
+    }
+
+    async fn set_public_access(&self, id: i64, public_access: bool) -> anyhow::Result<()> {
+        timed("users.set_public_access", self.inner.users().set_public_access(id, public_access)).await
+    }
+
+    async fn set_locale(&self, id: i64, locale: Option<&str>) -> anyhow::Result<()> {
+        timed("users.set_locale", self.inner.users().set_locale(id, locale)).await
+    }
+
+    async fn set_readonly(&self, id: i64, readonly: bool) -> anyhow::Result<()> {
+        timed("users.set_readonly", self.inner.users().set_readonly(id, readonly)).await
+    }
+
+    async fn set_password_hash(&self, id: i64, password_hash: &str) -> anyhow::Result<()> {
+        timed("users.set_password_hash", self.inner.users().set_password_hash(id, password_hash)).await
+    }
+
+    async fn delete(&self, id: i64) -> anyhow::Result<bool> {
+        timed("users.delete", self.inner.users().delete(id)).await
+    }
+}
+
+#[async_trait]
+impl TodoRepository for MeteredTodoRepository {
+    async fn find_by_id(&self, id: i64, user_id: i64) -> anyhow::Result<Option<Todo>> {
+        timed("todos.find_by_id", self.inner.todos().find_by_id(id, user_id)).await
+    }
+
+    async fn list_for_user(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        timed("todos.list_for_user", self.inner.todos().list_for_user(user_id)).await
+    }
+
+    async fn list_page(
+        &self,
+        user_id: i64,
+        filter: &TodoFilter,
+        after: Option<&TodoCursor>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Todo>> {
+        timed("todos.list_page", self.inner.todos().list_page(user_id, filter, after, limit)).await
+    }
+
+    async fn count(&self, user_id: i64, filter: &TodoFilter) -> anyhow::Result<i64> {
+        timed("todos.count", self.inner.todos().count(user_id, filter)).await
+    }
+
+    async fn search(&self, user_id: i64, terms: &SearchTerms, limit: i64) -> anyhow::Result<SearchResults> {
+        timed("todos.search", self.inner.todos().search(user_id, terms, limit)).await
+    }
+
+    async fn insert(&self, todo: NewTodo<'_>) -> anyhow::Result<Todo> {
+        timed("todos.insert", self.inner.todos().insert(todo)).await
+    }
+
+    async fn import(&self, todos: &[ImportedTodo<'_>]) -> anyhow::Result<u64> {
+        timed("todos.import", self.inner.todos().import(todos)).await
+    }
+
+    async fn update(&self, todo: &Todo) -> anyhow::Result<()> {
+        timed("todos.update", self.inner.todos().update(todo)).await
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> anyhow::Result<bool> {
+        timed("todos.delete", self.inner.todos().delete(id, user_id)).await
+    }
+
+    async fn trash(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        timed("todos.trash", self.inner.todos().trash(id, user_id, at)).await
+    }
+
+    async fn restore(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<Option<Todo>> {
+        timed("todos.restore", self.inner.todos().restore(id, user_id, at)).await
+    }
+
+    async fn list_trash(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        timed("todos.list_trash", self.inner.todos().list_trash(user_id)).await
+    }
+
+    async fn purge_trashed(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        timed("todos.purge_trashed", self.inner.todos().purge_trashed(before)).await
+    }
+
+    async fn history_buckets(&self, user_id: i64, days: i64) -> anyhow::Result<Vec<HistoryBucket>> {
+        timed("todos.history_buckets", self.inner.todos().history_buckets(user_id, days)).await
+    }
+
+    async fn list_created_on(&self, user_id: i64, date: &str) -> anyhow::Result<Vec<Todo>> {
+        timed("todos.list_created_on", self.inner.todos().list_created_on(user_id, date)).await
+    }
+
+    async fn totals(&self) -> anyhow::Result<TodoTotals> {
+        timed("todos.totals", self.inner.todos().totals()).await
+    }
+
+    async fn count_by_user(&self) -> anyhow::Result<HashMap<i64, i64>> {
+        timed("todos.count_by_user", self.inner.todos().count_by_user()).await
+    }
+}
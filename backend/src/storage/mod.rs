@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use std::{path::Path, sync::Arc};
+
+use crate::{
+    config::DatabaseConfig,
+    migrations::{AppliedMigration, Migration},
+    repository::{TodoRepository, UserRepository},
+};
+
+mod memory;
+mod metered;
+mod postgres;
+mod sqlite;
+
+pub use memory::MemoryStorage;
+pub use metered::MeteredStorage;
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+const USER_COLUMNS: &str = "id, username, password_hash, public_access, readonly, demo, locale, created_at";
+const TODO_COLUMNS: &str = "id, user_id, title, description, emoji, completed, due_date, created_at, updated_at, deleted_at";
+
+// LIKE的"包含"模式，转义通配符，配合 ESCAPE '\' 使用
+fn like_pattern(search: &str) -> String {
+    let mut pattern = String::with_capacity(search.len() + 2);
+    pattern.push('%');
+    for c in search.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+// 连接池使用情况（监控指标）
+#[derive(Debug)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+// 存储后端抽象：提供用户/任务仓库以及schema迁移，handler不关心具体数据库
+#[async_trait]
+pub trait Storage: Send + Sync {
+    // 后端名称，用于日志和状态展示
+    fn backend_name(&self) -> &'static str;
+
+    fn users(&self) -> &dyn UserRepository;
+    fn todos(&self) -> &dyn TodoRepository;
+
+    // 迁移
+    fn migrations(&self) -> &'static [Migration];
+    async fn prepare_legacy_schema(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>>;
+    async fn apply_migration(&self, migration: &Migration) -> anyhow::Result<()>;
+    async fn revert_migration(&self, migration: &Migration) -> anyhow::Result<()>;
+
+    // 在服务运行时生成一致的数据库快照
+    async fn backup(&self, _destination: &Path) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("{} 存储后端不支持内置备份", self.backend_name()))
+    }
+
+    // 检查数据库是否可用（就绪探针）
+    async fn ping(&self) -> anyhow::Result<()>;
+
+    // 数据完整性检查（管理工具），默认只检查连接
+    async fn check(&self) -> anyhow::Result<()> {
+        self.ping().await
+    }
+
+    // 回收空闲空间并更新统计信息
+    async fn vacuum(&self) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("{} 存储后端不支持VACUUM", self.backend_name()))
+    }
+
+    // 没有连接池的后端返回None
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+
+    async fn close(&self);
+}
+
+// 根据database.url的scheme选择存储后端
+pub async fn connect(config: &DatabaseConfig) -> anyhow::Result<Arc<dyn Storage>> {
+    if config.url.starts_with("postgres://") || config.url.starts_with("postgresql://") {
+        Ok(Arc::new(PostgresStorage::connect(config).await?))
+    } else if config.url.starts_with("sqlite:") {
+        Ok(Arc::new(SqliteStorage::connect(config).await?))
+    } else if config.url == "memory:" {
+        // 纯内存存储，进程退出后数据丢失，适合演示和测试
+        Ok(Arc::new(MemoryStorage::new()))
+    } else {
+        Err(anyhow::anyhow!("不支持的数据库URL: {}", config.url))
+    }
+}
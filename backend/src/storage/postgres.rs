@@ -0,0 +1,556 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::{PgPool, PgPoolOptions}, Executor, Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+
+use super::{like_pattern, PoolStats, Storage, TODO_COLUMNS, USER_COLUMNS};
+use crate::{
+    config::DatabaseConfig,
+    migrations::{AppliedMigration, Migration, POSTGRES_MIGRATIONS},
+    models::{Todo, User},
+    repository::{
+        HistoryBucket, ImportedTodo, NewTodo, NewUser, SearchResults, TodoCursor, TodoFilter, TodoRepository, TodoTotals,
+        UserRepository,
+    },
+    search::{self, SearchTerms},
+};
+
+pub struct PostgresStorage {
+    pool: PgPool,
+    users: PostgresUserRepository,
+    todos: PostgresTodoRepository,
+}
+
+pub struct PostgresUserRepository {
+    pool: PgPool,
+}
+
+pub struct PostgresTodoRepository {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub async fn connect(config: &DatabaseConfig) -> anyhow::Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .connect(&config.url)
+            .await?;
+
+        Ok(Self::new(pool))
+    }
+
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            users: PostgresUserRepository { pool: pool.clone() },
+            todos: PostgresTodoRepository { pool: pool.clone() },
+            pool,
+        }
+    }
+
+    async fn migrations_table_exists(&self) -> anyhow::Result<bool> {
+        let exists: bool = sqlx::query("SELECT to_regclass('schema_migrations') IS NOT NULL")
+            .fetch_one(&self.pool)
+            .await?
+            .get(0);
+        Ok(exists)
+    }
+
+    async fn ensure_migrations_table(&self) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    fn backend_name(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn users(&self) -> &dyn UserRepository {
+        &self.users
+    }
+
+    fn todos(&self) -> &dyn TodoRepository {
+        &self.todos
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        POSTGRES_MIGRATIONS
+    }
+
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>> {
+        if !self.migrations_table_exists().await? {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AppliedMigration {
+                version: row.get("version"),
+                name: row.get("name"),
+                applied_at: row.get("applied_at"),
+            })
+            .collect())
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        self.ensure_migrations_table().await?;
+
+        let mut tx = self.pool.begin().await?;
+        tx.execute(migration.up).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, applied_at) VALUES ($1, $2, $3)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn revert_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        tx.execute(migration.down).await?;
+        sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> anyhow::Result<()> {
+        sqlx::query("VACUUM ANALYZE").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats {
+            size: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+            max: self.pool.options().get_max_connections(),
+        })
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+#[async_trait]
+impl UserRepository for PostgresUserRepository {
+    async fn list(&self) -> anyhow::Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users", USER_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(users)
+    }
+
+    async fn find_by_id(&self, id: i64) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users WHERE username = $1", USER_COLUMNS))
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn first(&self) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users ORDER BY id LIMIT 1", USER_COLUMNS))
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn first_public(&self) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!(
+            "SELECT {} FROM users WHERE public_access = true ORDER BY id LIMIT 1",
+            USER_COLUMNS
+        ))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    async fn insert(&self, user: NewUser<'_>) -> anyhow::Result<User> {
+        let user = sqlx::query_as::<_, User>(&format!(
+            "INSERT INTO users (username, password_hash, public_access, readonly, demo) VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(user.username)
+        .bind(user.password_hash)
+        .bind(user.public_access)
+        .bind(user.readonly)
+        .bind(user.demo)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    async fn set_public_access(&self, id: i64, public_access: bool) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET public_access = $1 WHERE id = $2")
+            .bind(public_access)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_locale(&self, id: i64, locale: Option<&str>) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET locale = $1 WHERE id = $2")
+            .bind(locale)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_readonly(&self, id: i64, readonly: bool) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET readonly = $1 WHERE id = $2")
+            .bind(readonly)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_password_hash(&self, id: i64, password_hash: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // 任务通过外键 ON DELETE CASCADE 一起删除
+    async fn delete(&self, id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl TodoRepository for PostgresTodoRepository {
+    async fn find_by_id(&self, id: i64, user_id: i64) -> anyhow::Result<Option<Todo>> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+            TODO_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn list_for_user(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = $1 AND deleted_at IS NULL ORDER BY created_at DESC",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn list_page(
+        &self,
+        user_id: i64,
+        filter: &TodoFilter,
+        after: Option<&TodoCursor>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Todo>> {
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM todos", TODO_COLUMNS));
+        push_todo_filter(&mut query, user_id, filter);
+        if let Some(cursor) = after {
+            query
+                .push(" AND (created_at, id) < (")
+                .push_bind(cursor.created_at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        query.push(" ORDER BY created_at DESC, id DESC");
+
+        // 有q筛选时SQL只能缩小范围，需要在内存中按相同规则筛选后再截取
+        let Some(search) = &filter.search else {
+            query.push(" LIMIT ").push_bind(limit);
+            return Ok(query.build_query_as::<Todo>().fetch_all(&self.pool).await?);
+        };
+        let todos = query.build_query_as::<Todo>().fetch_all(&self.pool).await?;
+        Ok(todos
+            .into_iter()
+            .filter(|todo| search::todo_contains(todo, search))
+            .take(usize::try_from(limit).unwrap_or(0))
+            .collect())
+    }
+
+    async fn count(&self, user_id: i64, filter: &TodoFilter) -> anyhow::Result<i64> {
+        let Some(search) = &filter.search else {
+            let mut query = QueryBuilder::new("SELECT COUNT(*) FROM todos");
+            push_todo_filter(&mut query, user_id, filter);
+            return Ok(query.build_query_scalar::<i64>().fetch_one(&self.pool).await?);
+        };
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM todos", TODO_COLUMNS));
+        push_todo_filter(&mut query, user_id, filter);
+        let todos = query.build_query_as::<Todo>().fetch_all(&self.pool).await?;
+        Ok(todos.iter().filter(|todo| search::todo_contains(todo, search)).count() as i64)
+    }
+
+    // PostgreSQL没有FTS5索引，用ILIKE找出该用户的候选任务后在内存中筛选和排序。
+    // ILIKE对非ASCII字母是否区分大小写取决于数据库的LC_CTYPE，因此只用纯ASCII的词缩小范围
+    async fn search(&self, user_id: i64, terms: &SearchTerms, limit: i64) -> anyhow::Result<SearchResults> {
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM todos WHERE deleted_at IS NULL AND user_id = ", TODO_COLUMNS));
+        query.push_bind(user_id);
+        for term in terms.terms().iter().filter(|term| term.is_ascii()) {
+            let pattern = like_pattern(term);
+            query
+                .push(" AND (title ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR description ILIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        let todos = query.build_query_as::<Todo>().fetch_all(&self.pool).await?;
+        Ok(search::rank(todos, terms, limit))
+    }
+
+    async fn insert(&self, todo: NewTodo<'_>) -> anyhow::Result<Todo> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "INSERT INTO todos (user_id, title, description, emoji, due_date, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING {}",
+            TODO_COLUMNS
+        ))
+        .bind(todo.user_id)
+        .bind(todo.title)
+        .bind(todo.description)
+        .bind(todo.emoji)
+        .bind(todo.due_date)
+        .bind(todo.created_at)
+        .bind(todo.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn import(&self, todos: &[ImportedTodo<'_>]) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        for todo in todos {
+            sqlx::query(
+                "INSERT INTO todos (user_id, title, description, emoji, completed, due_date, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+            )
+            .bind(todo.user_id)
+            .bind(todo.title)
+            .bind(todo.description)
+            .bind(todo.emoji)
+            .bind(todo.completed)
+            .bind(todo.due_date)
+            .bind(todo.created_at)
+            .bind(todo.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(todos.len() as u64)
+    }
+
+    async fn update(&self, todo: &Todo) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE todos SET title = $1, description = $2, emoji = $3, completed = $4, due_date = $5, updated_at = $6 WHERE id = $7 AND user_id = $8 AND deleted_at IS NULL"
+        )
+        .bind(&todo.title)
+        .bind(&todo.description)
+        .bind(&todo.emoji)
+        .bind(todo.completed)
+        .bind(todo.due_date)
+        .bind(todo.updated_at)
+        .bind(todo.id)
+        .bind(todo.user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM todos WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn trash(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("UPDATE todos SET deleted_at = $1 WHERE id = $2 AND user_id = $3 AND deleted_at IS NULL")
+                .bind(at)
+                .bind(id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn restore(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<Option<Todo>> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "UPDATE todos SET deleted_at = NULL, updated_at = $1 WHERE id = $2 AND user_id = $3 AND deleted_at IS NOT NULL RETURNING {}",
+            TODO_COLUMNS
+        ))
+        .bind(at)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn list_trash(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = $1 AND deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn purge_trashed(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn history_buckets(&self, user_id: i64, days: i64) -> anyhow::Result<Vec<HistoryBucket>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                to_char((created_at AT TIME ZONE 'UTC')::date, 'YYYY-MM-DD') as date,
+                COUNT(*) as count,
+                COUNT(*) FILTER (WHERE completed) as completed_count
+            FROM todos
+            WHERE user_id = $1
+                AND deleted_at IS NULL
+                AND created_at >= (date_trunc('day', NOW() AT TIME ZONE 'UTC') - make_interval(days => $2::int)) AT TIME ZONE 'UTC'
+            GROUP BY 1
+            ORDER BY 1
+            "#
+        )
+        .bind(user_id)
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| HistoryBucket {
+                date: row.get("date"),
+                count: row.get("count"),
+                completed_count: row.get("completed_count"),
+            })
+            .collect())
+    }
+
+    async fn list_created_on(&self, user_id: i64, date: &str) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = $1 AND deleted_at IS NULL AND (created_at AT TIME ZONE 'UTC')::date = $2::date ORDER BY created_at",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn totals(&self) -> anyhow::Result<TodoTotals> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE NOT completed) as open,
+                COUNT(*) FILTER (WHERE completed) as completed
+            FROM todos
+            WHERE deleted_at IS NULL
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(TodoTotals {
+            open: row.get("open"),
+            completed: row.get("completed"),
+        })
+    }
+
+    async fn count_by_user(&self) -> anyhow::Result<HashMap<i64, i64>> {
+        let rows = sqlx::query("SELECT user_id, COUNT(*) as count FROM todos WHERE deleted_at IS NULL GROUP BY user_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|row| (row.get("user_id"), row.get("count"))).collect())
+    }
+}
+
+// 按筛选条件拼接WHERE子句
+fn push_todo_filter(query: &mut QueryBuilder<'_, Postgres>, user_id: i64, filter: &TodoFilter) {
+    query.push(" WHERE deleted_at IS NULL AND user_id = ").push_bind(user_id);
+    if let Some(completed) = filter.completed {
+        query.push(" AND completed = ").push_bind(completed);
+    }
+    if let Some(created_after) = filter.created_after {
+        query.push(" AND created_at > ").push_bind(created_after);
+    }
+    if let Some(updated_since) = filter.updated_since {
+        query.push(" AND updated_at >= ").push_bind(updated_since);
+    }
+    if let Some(emoji) = &filter.emoji {
+        query.push(" AND emoji = ").push_bind(emoji.clone());
+    }
+    // 与search相同，ILIKE对非ASCII字母的处理取决于LC_CTYPE，只用纯ASCII的文字缩小范围，
+    // 调用方还要用search::todo_contains筛选
+    if let Some(search) = filter.search.as_ref().filter(|search| search.is_ascii()) {
+        let pattern = like_pattern(search);
+        query
+            .push(" AND (title ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR description ILIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+}
@@ -0,0 +1,848 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use libsqlite3_sys as ffi;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous},
+    Executor, FromRow, QueryBuilder, Row, Sqlite,
+};
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    os::raw::{c_char, c_int},
+    path::Path,
+    ptr::NonNull,
+    str::FromStr,
+    time::Duration,
+};
+use tracing::info;
+
+use super::{like_pattern, PoolStats, Storage, TODO_COLUMNS, USER_COLUMNS};
+use crate::{
+    config::DatabaseConfig,
+    migrations::{AppliedMigration, Migration, SQLITE_MIGRATIONS},
+    models::{Todo, User},
+    repository::{
+        HistoryBucket, ImportedTodo, NewTodo, NewUser, ScoredTodo, SearchResults, TodoCursor, TodoFilter, TodoRepository, TodoTotals,
+        UserRepository,
+    },
+    search::{self, SearchTerms},
+};
+
+pub struct SqliteStorage {
+    pool: SqlitePool,
+    users: SqliteUserRepository,
+    todos: SqliteTodoRepository,
+}
+
+pub struct SqliteUserRepository {
+    pool: SqlitePool,
+}
+
+pub struct SqliteTodoRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub async fn connect(config: &DatabaseConfig) -> anyhow::Result<Self> {
+        let journal_mode = SqliteJournalMode::from_str(&config.journal_mode)
+            .map_err(|_| anyhow::anyhow!("无效的journal_mode: {}", config.journal_mode))?;
+        let synchronous = SqliteSynchronous::from_str(&config.synchronous)
+            .map_err(|_| anyhow::anyhow!("无效的synchronous: {}", config.synchronous))?;
+
+        // 创建数据库连接选项，根据配置决定是否自动创建数据库文件
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .create_if_missing(config.create_if_missing)
+            .journal_mode(journal_mode)
+            .synchronous(synchronous)
+            .busy_timeout(Duration::from_millis(config.busy_timeout_ms))
+            .foreign_keys(config.foreign_keys);
+
+        // 创建数据库连接池
+        let pool = pool_options()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self::new(pool))
+    }
+
+    // 打开已有的数据库文件，文件不存在时失败（用于校验备份）。
+    // 使用回滚日志模式，关闭后不会在旁边留下-wal/-shm文件
+    pub async fn open_existing(path: &Path) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(false)
+            .journal_mode(SqliteJournalMode::Delete);
+        let pool = pool_options().connect_with(options).await?;
+
+        Ok(Self::new(pool))
+    }
+
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            users: SqliteUserRepository { pool: pool.clone() },
+            todos: SqliteTodoRepository { pool: pool.clone() },
+            pool,
+        }
+    }
+
+    pub async fn integrity_check(&self) -> anyhow::Result<()> {
+        let result: String = sqlx::query("PRAGMA integrity_check")
+            .fetch_one(&self.pool)
+            .await?
+            .get(0);
+
+        if result != "ok" {
+            return Err(anyhow::anyhow!("数据库完整性检查失败: {}", result));
+        }
+        Ok(())
+    }
+
+    async fn migrations_table_exists(&self) -> anyhow::Result<bool> {
+        let exists = sqlx::query(
+            "SELECT COUNT(*) as count FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'"
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .get::<i64, _>("count") > 0;
+        Ok(exists)
+    }
+
+    async fn ensure_migrations_table(&self) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at DATETIME NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    fn backend_name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn users(&self) -> &dyn UserRepository {
+        &self.users
+    }
+
+    fn todos(&self) -> &dyn TodoRepository {
+        &self.todos
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        SQLITE_MIGRATIONS
+    }
+
+    // 兼容引入迁移系统之前创建的数据库：旧版本可能缺少readonly字段
+    async fn prepare_legacy_schema(&self) -> anyhow::Result<()> {
+        let users_exists = sqlx::query(
+            "SELECT COUNT(*) as count FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .get::<i64, _>("count") > 0;
+
+        if !users_exists {
+            return Ok(());
+        }
+
+        let has_readonly = sqlx::query(
+            "SELECT COUNT(*) as count FROM pragma_table_info('users') WHERE name = 'readonly'"
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .get::<i64, _>("count") > 0;
+
+        if !has_readonly {
+            sqlx::query("ALTER TABLE users ADD COLUMN readonly BOOLEAN NOT NULL DEFAULT FALSE")
+                .execute(&self.pool)
+                .await?;
+            info!("旧版数据库已补充readonly字段");
+        }
+
+        Ok(())
+    }
+
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>> {
+        if !self.migrations_table_exists().await? {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AppliedMigration {
+                version: row.get("version"),
+                name: row.get("name"),
+                applied_at: row.get("applied_at"),
+            })
+            .collect())
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        self.ensure_migrations_table().await?;
+
+        let mut tx = self.pool.begin().await?;
+        tx.execute(migration.up).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, applied_at) VALUES (?, ?, ?)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn revert_migration(&self, migration: &Migration) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        tx.execute(migration.down).await?;
+        sqlx::query("DELETE FROM schema_migrations WHERE version = ?")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn backup(&self, destination: &Path) -> anyhow::Result<()> {
+        // 以create_new创建目标文件，已存在时失败，不会覆盖其它进程同时写入的文件
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(destination) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(anyhow::anyhow!("备份目标已存在: {}", destination.display()));
+            }
+            Err(e) => return Err(anyhow::anyhow!("无法创建备份文件 {}: {}", destination.display(), e)),
+        }
+
+        let result = async {
+            let mut conn = self.pool.acquire().await?;
+            let mut handle = conn.lock_handle().await?;
+            let backup = OnlineBackup::start(handle.as_raw_handle(), destination)?;
+            online_backup(backup).await
+        }
+        .await;
+
+        if result.is_err() {
+            // 清理不完整的备份文件
+            let _ = std::fs::remove_file(destination);
+        }
+        result
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        self.integrity_check().await?;
+
+        let violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&self.pool)
+            .await?
+            .len();
+        if violations > 0 {
+            return Err(anyhow::anyhow!("发现 {} 条违反外键约束的记录", violations));
+        }
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> anyhow::Result<()> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats {
+            size: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+            max: self.pool.options().get_max_connections(),
+        })
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+// 每个新连接都注册自定义函数
+fn pool_options() -> SqlitePoolOptions {
+    SqlitePoolOptions::new().after_connect(|conn, _meta| {
+        Box::pin(async move {
+            let mut handle = conn.lock_handle().await?;
+            register_functions(handle.as_raw_handle()).map_err(|e| sqlx::Error::Configuration(e.into()))
+        })
+    })
+}
+
+// SQLite自带的LIKE和lower()只处理ASCII字母，注册casefold(text)使筛选和搜索与其它存储后端一样
+// 按Unicode规则不区分大小写
+fn register_functions(db: NonNull<ffi::sqlite3>) -> anyhow::Result<()> {
+    let name = CString::new("casefold")?;
+    let rc = unsafe {
+        ffi::sqlite3_create_function_v2(
+            db.as_ptr(),
+            name.as_ptr(),
+            1,
+            ffi::SQLITE_UTF8 | ffi::SQLITE_DETERMINISTIC,
+            std::ptr::null_mut(),
+            Some(casefold),
+            None,
+            None,
+            None,
+        )
+    };
+    if rc != ffi::SQLITE_OK {
+        return Err(anyhow::anyhow!("注册SQL函数casefold失败: {}", unsafe { sqlite_error_message(db.as_ptr()) }));
+    }
+    Ok(())
+}
+
+unsafe extern "C" fn casefold(ctx: *mut ffi::sqlite3_context, _argc: c_int, argv: *mut *mut ffi::sqlite3_value) {
+    let value = *argv;
+    if ffi::sqlite3_value_type(value) == ffi::SQLITE_NULL {
+        ffi::sqlite3_result_null(ctx);
+        return;
+    }
+    // 先取文本再取长度，长度才是转换为UTF-8之后的字节数
+    let text = ffi::sqlite3_value_text(value);
+    let bytes = match usize::try_from(ffi::sqlite3_value_bytes(value)) {
+        Ok(len) if !text.is_null() => std::slice::from_raw_parts(text, len),
+        _ => &[],
+    };
+    let folded = search::fold_case(&String::from_utf8_lossy(bytes));
+    match c_int::try_from(folded.len()) {
+        Ok(len) => ffi::sqlite3_result_text(ctx, folded.as_ptr() as *const c_char, len, ffi::SQLITE_TRANSIENT()),
+        Err(_) => ffi::sqlite3_result_error_toobig(ctx),
+    }
+}
+
+// 在线备份每一步复制的页数，两步之间让出线程，避免长时间阻塞异步运行时
+const BACKUP_PAGES_PER_STEP: c_int = 256;
+// 源数据库被锁定时的重试间隔和次数
+const BACKUP_BUSY_RETRY: Duration = Duration::from_millis(20);
+const BACKUP_BUSY_RETRIES: u32 = 250;
+
+// 使用SQLite在线备份API将整个数据库复制到已创建的空文件，期间其它连接仍可正常读写。
+// 其它连接在备份过程中写入时，SQLite会在下一步自动重新开始复制，最终得到一致的快照
+async fn online_backup(backup: OnlineBackup) -> anyhow::Result<()> {
+    let mut busy_retries = 0;
+    loop {
+        match backup.step(BACKUP_PAGES_PER_STEP) {
+            ffi::SQLITE_DONE => return backup.finish(),
+            ffi::SQLITE_OK => tokio::task::yield_now().await,
+            ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if busy_retries < BACKUP_BUSY_RETRIES => {
+                busy_retries += 1;
+                tokio::time::sleep(BACKUP_BUSY_RETRY).await;
+            }
+            ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => return Err(anyhow::anyhow!("数据库忙，请稍后重试")),
+            _ => return Err(anyhow::anyhow!("备份失败: {}", backup.error_message())),
+        }
+    }
+}
+
+// 进行中的在线备份，drop时释放备份对象并关闭目标数据库（包括中途出错或被取消的情况）
+struct OnlineBackup {
+    dest: *mut ffi::sqlite3,
+    backup: *mut ffi::sqlite3_backup,
+}
+
+// 两个指针只由持有源连接锁的备份任务使用
+unsafe impl Send for OnlineBackup {}
+
+impl OnlineBackup {
+    fn start(source: NonNull<ffi::sqlite3>, destination: &Path) -> anyhow::Result<Self> {
+        let path = CString::new(destination.to_string_lossy().as_bytes())?;
+        let main = CString::new("main")?;
+
+        unsafe {
+            let mut dest: *mut ffi::sqlite3 = std::ptr::null_mut();
+            let rc = ffi::sqlite3_open_v2(path.as_ptr(), &mut dest, ffi::SQLITE_OPEN_READWRITE, std::ptr::null());
+            if rc != ffi::SQLITE_OK {
+                let message = sqlite_error_message(dest);
+                ffi::sqlite3_close(dest);
+                return Err(anyhow::anyhow!("无法打开备份文件: {}", message));
+            }
+
+            let backup = ffi::sqlite3_backup_init(dest, main.as_ptr(), source.as_ptr(), main.as_ptr());
+            if backup.is_null() {
+                let message = sqlite_error_message(dest);
+                ffi::sqlite3_close(dest);
+                return Err(anyhow::anyhow!("初始化备份失败: {}", message));
+            }
+            Ok(Self { dest, backup })
+        }
+    }
+
+    fn step(&self, pages: c_int) -> c_int {
+        unsafe { ffi::sqlite3_backup_step(self.backup, pages) }
+    }
+
+    fn error_message(&self) -> String {
+        unsafe { sqlite_error_message(self.dest) }
+    }
+
+    fn finish(mut self) -> anyhow::Result<()> {
+        let rc = unsafe { ffi::sqlite3_backup_finish(self.backup) };
+        self.backup = std::ptr::null_mut();
+        if rc != ffi::SQLITE_OK {
+            return Err(anyhow::anyhow!("备份失败: {}", self.error_message()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OnlineBackup {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.backup.is_null() {
+                ffi::sqlite3_backup_finish(self.backup);
+            }
+            ffi::sqlite3_close(self.dest);
+        }
+    }
+}
+
+unsafe fn sqlite_error_message(db: *mut ffi::sqlite3) -> String {
+    if db.is_null() {
+        return "内存不足".to_string();
+    }
+    std::ffi::CStr::from_ptr(ffi::sqlite3_errmsg(db))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[async_trait]
+impl UserRepository for SqliteUserRepository {
+    async fn list(&self) -> anyhow::Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users", USER_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(users)
+    }
+
+    async fn find_by_id(&self, id: i64) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users WHERE username = ?", USER_COLUMNS))
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn first(&self) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users ORDER BY id LIMIT 1", USER_COLUMNS))
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn first_public(&self) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!(
+            "SELECT {} FROM users WHERE public_access = true ORDER BY id LIMIT 1",
+            USER_COLUMNS
+        ))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    async fn insert(&self, user: NewUser<'_>) -> anyhow::Result<User> {
+        let user = sqlx::query_as::<_, User>(&format!(
+            "INSERT INTO users (username, password_hash, public_access, readonly, demo) VALUES (?, ?, ?, ?, ?) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(user.username)
+        .bind(user.password_hash)
+        .bind(user.public_access)
+        .bind(user.readonly)
+        .bind(user.demo)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    async fn set_public_access(&self, id: i64, public_access: bool) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET public_access = ? WHERE id = ?")
+            .bind(public_access)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_locale(&self, id: i64, locale: Option<&str>) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET locale = ? WHERE id = ?")
+            .bind(locale)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_readonly(&self, id: i64, readonly: bool) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET readonly = ? WHERE id = ?")
+            .bind(readonly)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_password_hash(&self, id: i64, password_hash: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+            .bind(password_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // 显式删除任务，不依赖 foreign_keys 配置
+    async fn delete(&self, id: i64) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM todos WHERE user_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM users WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl TodoRepository for SqliteTodoRepository {
+    async fn find_by_id(&self, id: i64, user_id: i64) -> anyhow::Result<Option<Todo>> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+            TODO_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn list_for_user(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = ? AND deleted_at IS NULL ORDER BY created_at DESC",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn list_page(
+        &self,
+        user_id: i64,
+        filter: &TodoFilter,
+        after: Option<&TodoCursor>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Todo>> {
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM todos", TODO_COLUMNS));
+        push_todo_filter(&mut query, user_id, filter);
+        if let Some(cursor) = after {
+            query
+                .push(" AND (created_at < ")
+                .push_bind(cursor.created_at)
+                .push(" OR (created_at = ")
+                .push_bind(cursor.created_at)
+                .push(" AND id < ")
+                .push_bind(cursor.id)
+                .push("))");
+        }
+        query.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(limit);
+
+        let todos = query.build_query_as::<Todo>().fetch_all(&self.pool).await?;
+        Ok(todos)
+    }
+
+    async fn count(&self, user_id: i64, filter: &TodoFilter) -> anyhow::Result<i64> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM todos");
+        push_todo_filter(&mut query, user_id, filter);
+        let count = query.build_query_scalar::<i64>().fetch_one(&self.pool).await?;
+        Ok(count)
+    }
+
+    async fn search(&self, user_id: i64, terms: &SearchTerms, limit: i64) -> anyhow::Result<SearchResults> {
+        let Some(fts_query) = terms.fts_query() else {
+            // 搜索词都太短，无法使用trigram索引，退回到只扫描该用户任务的LIKE匹配
+            let mut query = QueryBuilder::new(format!("SELECT {} FROM todos WHERE deleted_at IS NULL AND user_id = ", TODO_COLUMNS));
+            query.push_bind(user_id);
+            push_like_terms(&mut query, terms.unindexed());
+            let todos = query.build_query_as::<Todo>().fetch_all(&self.pool).await?;
+            return Ok(search::rank(todos, terms, limit));
+        };
+
+        // bm25越小越相关，取负数作为分数；标题列的权重高于描述
+        let mut query = QueryBuilder::new(format!(
+            "SELECT {}, -m.relevance AS score FROM todos JOIN (SELECT rowid, bm25(todos_fts, {:.1}, 1.0) AS relevance FROM todos_fts WHERE todos_fts MATCH ",
+            TODO_COLUMNS,
+            search::TITLE_WEIGHT
+        ));
+        query.push_bind(fts_query.clone()).push(") AS m ON m.rowid = todos.id WHERE deleted_at IS NULL AND user_id = ");
+        query.push_bind(user_id);
+        push_like_terms(&mut query, terms.unindexed());
+        query.push(" ORDER BY m.relevance, created_at DESC, id DESC LIMIT ").push_bind(limit);
+
+        let hits = query
+            .build()
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| {
+                Ok(ScoredTodo {
+                    todo: Todo::from_row(row)?,
+                    score: row.try_get("score")?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut query = QueryBuilder::new(
+            "SELECT COUNT(*) FROM todos JOIN (SELECT rowid FROM todos_fts WHERE todos_fts MATCH ",
+        );
+        query.push_bind(fts_query).push(") AS m ON m.rowid = todos.id WHERE deleted_at IS NULL AND user_id = ");
+        query.push_bind(user_id);
+        push_like_terms(&mut query, terms.unindexed());
+        let total = query.build_query_scalar::<i64>().fetch_one(&self.pool).await?;
+
+        Ok(SearchResults { hits, total })
+    }
+
+    async fn insert(&self, todo: NewTodo<'_>) -> anyhow::Result<Todo> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "INSERT INTO todos (user_id, title, description, emoji, due_date, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+            TODO_COLUMNS
+        ))
+        .bind(todo.user_id)
+        .bind(todo.title)
+        .bind(todo.description)
+        .bind(todo.emoji)
+        .bind(todo.due_date)
+        .bind(todo.created_at)
+        .bind(todo.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn import(&self, todos: &[ImportedTodo<'_>]) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        for todo in todos {
+            sqlx::query(
+                "INSERT INTO todos (user_id, title, description, emoji, completed, due_date, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(todo.user_id)
+            .bind(todo.title)
+            .bind(todo.description)
+            .bind(todo.emoji)
+            .bind(todo.completed)
+            .bind(todo.due_date)
+            .bind(todo.created_at)
+            .bind(todo.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(todos.len() as u64)
+    }
+
+    async fn update(&self, todo: &Todo) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE todos SET title = ?, description = ?, emoji = ?, completed = ?, due_date = ?, updated_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NULL"
+        )
+        .bind(&todo.title)
+        .bind(&todo.description)
+        .bind(&todo.emoji)
+        .bind(todo.completed)
+        .bind(todo.due_date)
+        .bind(todo.updated_at)
+        .bind(todo.id)
+        .bind(todo.user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM todos WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn trash(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        let result = sqlx::query("UPDATE todos SET deleted_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NULL")
+            .bind(at)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn restore(&self, id: i64, user_id: i64, at: DateTime<Utc>) -> anyhow::Result<Option<Todo>> {
+        let todo = sqlx::query_as::<_, Todo>(&format!(
+            "UPDATE todos SET deleted_at = NULL, updated_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL RETURNING {}",
+            TODO_COLUMNS
+        ))
+        .bind(at)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(todo)
+    }
+
+    async fn list_trash(&self, user_id: i64) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = ? AND deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn purge_trashed(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn history_buckets(&self, user_id: i64, days: i64) -> anyhow::Result<Vec<HistoryBucket>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                DATE(created_at) as date,
+                COUNT(*) as count,
+                SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END) as completed_count
+            FROM todos
+            WHERE user_id = ? AND deleted_at IS NULL AND created_at >= DATE('now', ?)
+            GROUP BY DATE(created_at)
+            ORDER BY date
+            "#
+        )
+        .bind(user_id)
+        .bind(format!("-{} days", days))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| HistoryBucket {
+                date: row.get("date"),
+                count: row.get("count"),
+                completed_count: row.get("completed_count"),
+            })
+            .collect())
+    }
+
+    async fn list_created_on(&self, user_id: i64, date: &str) -> anyhow::Result<Vec<Todo>> {
+        let todos = sqlx::query_as::<_, Todo>(&format!(
+            "SELECT {} FROM todos WHERE user_id = ? AND deleted_at IS NULL AND DATE(created_at) = ? ORDER BY created_at",
+            TODO_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(todos)
+    }
+
+    async fn totals(&self) -> anyhow::Result<TodoTotals> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(SUM(CASE WHEN completed = 0 THEN 1 ELSE 0 END), 0) as open,
+                COALESCE(SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END), 0) as completed
+            FROM todos
+            WHERE deleted_at IS NULL
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(TodoTotals {
+            open: row.get("open"),
+            completed: row.get("completed"),
+        })
+    }
+
+    async fn count_by_user(&self) -> anyhow::Result<HashMap<i64, i64>> {
+        let rows = sqlx::query("SELECT user_id, COUNT(*) as count FROM todos WHERE deleted_at IS NULL GROUP BY user_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|row| (row.get("user_id"), row.get("count"))).collect())
+    }
+}
+
+// 每个搜索词都要出现在标题或描述中；两边都经过casefold，非ASCII字母同样不区分大小写
+fn push_like_terms<'a>(query: &mut QueryBuilder<'_, Sqlite>, terms: impl Iterator<Item = &'a str>) {
+    for term in terms {
+        let pattern = like_pattern(&search::fold_case(term));
+        query
+            .push(" AND (casefold(title) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR casefold(description) LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+}
+
+// 按筛选条件拼接WHERE子句
+fn push_todo_filter(query: &mut QueryBuilder<'_, Sqlite>, user_id: i64, filter: &TodoFilter) {
+    query.push(" WHERE deleted_at IS NULL AND user_id = ").push_bind(user_id);
+    if let Some(completed) = filter.completed {
+        query.push(" AND completed = ").push_bind(completed);
+    }
+    if let Some(created_after) = filter.created_after {
+        query.push(" AND created_at > ").push_bind(created_after);
+    }
+    if let Some(updated_since) = filter.updated_since {
+        query.push(" AND updated_at >= ").push_bind(updated_since);
+    }
+    if let Some(emoji) = &filter.emoji {
+        query.push(" AND emoji = ").push_bind(emoji.clone());
+    }
+    if let Some(search) = &filter.search {
+        push_like_terms(query, std::iter::once(search.as_str()));
+    }
+}
@@ -0,0 +1,101 @@
+use axum::{
+    extract::Host,
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use std::{fs, path::Path, time::{Duration, SystemTime}};
+use tracing::{error, info, warn};
+
+use crate::config::TlsConfig;
+
+// 加载证书和私钥
+pub async fn load(config: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    RustlsConfig::from_pem_file(&config.cert_path, &config.key_path)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "无法加载TLS证书 {} / 私钥 {}: {}",
+                config.cert_path,
+                config.key_path,
+                e
+            )
+        })
+}
+
+// 定期检查证书文件的修改时间，变化后重新加载；加载失败时继续使用旧证书
+pub fn spawn_reload(rustls: RustlsConfig, config: TlsConfig) {
+    if config.reload_interval_secs == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut loaded = modified_times(&config);
+        let mut interval = tokio::time::interval(Duration::from_secs(config.reload_interval_secs));
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let current = modified_times(&config);
+            if current == loaded {
+                continue;
+            }
+
+            match rustls.reload_from_pem_file(&config.cert_path, &config.key_path).await {
+                Ok(()) => {
+                    info!("TLS证书已重新加载: {}", config.cert_path);
+                    loaded = current;
+                }
+                // 证书和私钥可能没有同时更新完，下次检查时重试
+                Err(e) => warn!("重新加载TLS证书失败，继续使用旧证书: {}", e),
+            }
+        }
+    });
+}
+
+fn modified_times(config: &TlsConfig) -> (Option<SystemTime>, Option<SystemTime>) {
+    let modified = |path: &str| fs::metadata(Path::new(path)).and_then(|meta| meta.modified()).ok();
+    (modified(&config.cert_path), modified(&config.key_path))
+}
+
+// 在HTTP端口上把所有请求永久重定向到HTTPS
+pub fn spawn_redirect(host: String, http_port: u16, https_port: u16) {
+    let app = Router::new().fallback(move |host: Host, uri: Uri| redirect_to_https(host, uri, https_port));
+
+    tokio::spawn(async move {
+        let address = format!("{}:{}", host, http_port);
+        let listener = match tokio::net::TcpListener::bind(&address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("HTTP重定向端口 {} 监听失败: {}", address, e);
+                return;
+            }
+        };
+
+        info!("HTTP重定向监听在 {}", address);
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("HTTP重定向服务异常退出: {}", e);
+        }
+    });
+}
+
+async fn redirect_to_https(Host(host): Host, uri: Uri, https_port: u16) -> Response {
+    // 去掉HTTP端口，换成HTTPS端口（443时省略）；IPv6地址形如 [::1]:80
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host.as_str(),
+    };
+    let authority = if https_port == 443 {
+        hostname.to_string()
+    } else {
+        format!("{}:{}", hostname, https_port)
+    };
+
+    let path = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    match format!("https://{}{}", authority, path).parse::<Uri>() {
+        Ok(target) => Redirect::permanent(&target.to_string()).into_response(),
+        Err(_) => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::{config::TrashConfig, storage::Storage};
+
+// 永久删除超过保留期限的任务，返回删除的数量；保留天数为0时不删除
+pub async fn purge_expired(storage: &dyn Storage, config: &TrashConfig, now: DateTime<Utc>) -> anyhow::Result<u64> {
+    if config.retention_days == 0 {
+        return Ok(0);
+    }
+    let before = Duration::try_days(i64::from(config.retention_days))
+        .and_then(|retention| now.checked_sub_signed(retention))
+        .ok_or_else(|| anyhow::anyhow!("trash.retention_days 超出范围: {}", config.retention_days))?;
+    storage.todos().purge_trashed(before).await
+}
+
+// 按配置的间隔定时清理回收站，启动时先清理一次
+pub fn spawn_periodic_purge(storage: Arc<dyn Storage>, config: TrashConfig) {
+    if config.retention_days == 0 {
+        info!("回收站中的任务将永久保留");
+        return;
+    }
+    // 加载配置时已检查过，这里防止直接构造的配置
+    if let Err(e) = config.validate() {
+        warn!("{}，回收站清理未启动", e);
+        return;
+    }
+    if config.purge_interval_minutes == 0 {
+        warn!("trash.purge_interval_minutes 必须大于0，回收站清理未启动");
+        return;
+    }
+    info!(
+        "回收站: 任务保留 {} 天，每 {} 分钟清理一次",
+        config.retention_days, config.purge_interval_minutes
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.purge_interval_minutes * 60));
+        loop {
+            interval.tick().await;
+            match purge_expired(storage.as_ref(), &config, Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => info!("已永久删除回收站中过期的 {} 个任务", count),
+                Err(e) => error!("清理回收站失败: {}", e),
+            }
+        }
+    });
+}
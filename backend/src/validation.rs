@@ -0,0 +1,259 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::request::Parts,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    error::{AppError, AppJson, AppQuery},
+    i18n::{self, Locale},
+    repository::TodoCursor,
+};
+
+// 长度限制按字符（而不是字节）计算
+pub const USERNAME_MIN_CHARS: usize = 3;
+pub const USERNAME_MAX_CHARS: usize = 32;
+pub const PASSWORD_MIN_CHARS: usize = 6;
+// bcrypt只使用密码的前72个字节，更长的部分会被静默忽略
+pub const PASSWORD_MAX_BYTES: usize = 72;
+pub const TITLE_MAX_CHARS: usize = 200;
+pub const DESCRIPTION_MAX_CHARS: usize = 2000;
+
+// 分页时每页的任务数量
+pub const PAGE_DEFAULT_LIMIT: u32 = 50;
+pub const PAGE_MAX_LIMIT: u32 = 200;
+// 搜索时返回的结果数量
+pub const SEARCH_DEFAULT_LIMIT: u32 = 20;
+pub const SEARCH_MAX_LIMIT: u32 = 100;
+
+// 日期统一使用 YYYY-MM-DD 格式
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// 单个字段的校验错误，code供客户端判断，message供直接展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: Cow<'static, str>,
+    pub code: Cow<'static, str>,
+    pub message: String,
+}
+
+// 请求模型的校验：先规范化（如去掉首尾空白），再检查规则
+pub trait Validate {
+    fn validate(&mut self) -> Result<(), Vec<FieldError>>;
+}
+
+// 收集一个请求中所有字段的错误，一次性返回给客户端
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, field: &'static str, code: &'static str, args: &[(&str, &dyn Display)]) {
+        self.add_with_message(field, code, code, args);
+    }
+
+    // 提示文字按当前请求的语言生成
+    fn add_with_message(&mut self, field: &'static str, code: &'static str, message: &str, args: &[(&str, &dyn Display)]) {
+        let message = i18n::t_args(&format!("validation.{}", message), args);
+        self.errors.push(FieldError {
+            field: field.into(),
+            code: code.into(),
+            message,
+        });
+    }
+
+    pub fn required(&mut self, field: &'static str, value: &str) -> bool {
+        if value.is_empty() {
+            self.add(field, "REQUIRED", &[]);
+            return false;
+        }
+        true
+    }
+
+    pub fn length(&mut self, field: &'static str, value: &str, min: usize, max: usize) {
+        let chars = value.chars().count();
+        if chars < min {
+            self.add(field, "TOO_SHORT", &[("min", &min)]);
+        } else if chars > max {
+            self.add(field, "TOO_LONG", &[("max", &max)]);
+        }
+    }
+
+    // 用户名只允许字母、数字、下划线、连字符和点
+    pub fn username(&mut self, field: &'static str, value: &str) {
+        if !self.required(field, value) {
+            return;
+        }
+        self.length(field, value, USERNAME_MIN_CHARS, USERNAME_MAX_CHARS);
+        if !value.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+            self.add(field, "INVALID_CHARS", &[]);
+        }
+    }
+
+    pub fn password(&mut self, field: &'static str, value: &str) {
+        if !self.required(field, value) {
+            return;
+        }
+        if value.chars().count() < PASSWORD_MIN_CHARS {
+            self.add(field, "TOO_SHORT", &[("min", &PASSWORD_MIN_CHARS)]);
+        } else if value.len() > PASSWORD_MAX_BYTES {
+            self.add_with_message(field, "TOO_LONG", "TOO_LONG_BYTES", &[("max", &PASSWORD_MAX_BYTES)]);
+        }
+    }
+
+    pub fn title(&mut self, field: &'static str, value: &str) {
+        if self.required(field, value) {
+            self.length(field, value, 1, TITLE_MAX_CHARS);
+        }
+    }
+
+    pub fn description(&mut self, field: &'static str, value: &str) {
+        self.length(field, value, 0, DESCRIPTION_MAX_CHARS);
+    }
+
+    // emoji必须是单个字形簇（组合emoji如👨‍👩‍👧、🇨🇳也算一个），且不能是普通ASCII字符
+    pub fn emoji(&mut self, field: &'static str, value: &str) {
+        if !self.required(field, value) {
+            return;
+        }
+        let mut graphemes = value.graphemes(true);
+        let single = graphemes.next().is_some() && graphemes.next().is_none();
+        if !single || value.is_ascii() || value.chars().any(char::is_control) {
+            self.add(field, "INVALID_EMOJI", &[]);
+        }
+    }
+
+    pub fn date(&mut self, field: &'static str, value: &str) -> Option<NaiveDate> {
+        let date = parse_date(value);
+        if date.is_none() {
+            self.add(field, "INVALID_DATE", &[]);
+        }
+        date
+    }
+
+    // RFC 3339时间，或 YYYY-MM-DD 表示当天0点（UTC）
+    pub fn timestamp(&mut self, field: &'static str, value: &str) -> Option<DateTime<Utc>> {
+        let timestamp = parse_timestamp(value);
+        if timestamp.is_none() {
+            self.add(field, "INVALID_DATETIME", &[]);
+        }
+        timestamp
+    }
+
+    pub fn range(&mut self, field: &'static str, value: u32, min: u32, max: u32) {
+        if value < min || value > max {
+            self.add(field, "OUT_OF_RANGE", &[("min", &min), ("max", &max)]);
+        }
+    }
+
+    pub fn cursor(&mut self, field: &'static str, value: &str) -> Option<TodoCursor> {
+        let cursor = TodoCursor::decode(value);
+        if cursor.is_none() {
+            self.add(field, "INVALID_CURSOR", &[]);
+        }
+        cursor
+    }
+
+    // 语言标签规范化为支持的语言，如 en-US -> en
+    pub fn locale(&mut self, field: &'static str, value: &str) -> Option<Locale> {
+        let locale = Locale::parse(value);
+        if locale.is_none() {
+            self.add(field, "UNSUPPORTED_LOCALE", &[("locales", &Locale::supported_tags())]);
+        }
+        locale
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+}
+
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    parse_date(value).map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+}
+
+// 去掉首尾空白，只有空白的可选字段视为未填写
+pub fn trim(value: &mut String) {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+}
+
+pub fn trim_optional(value: &mut Option<String>) {
+    if let Some(inner) = value {
+        trim(inner);
+        if inner.is_empty() {
+            *value = None;
+        }
+    }
+}
+
+// 查询参数中的空值视为未设置：表单会提交所有字段，如 ?q=&completed=&limit=
+pub fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+// 解析JSON请求体并校验，校验失败时返回字段级错误
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let AppJson(mut value) = AppJson::<T>::from_request(request, state).await?;
+        value.validate().map_err(AppError::validation)?;
+        Ok(ValidJson(value))
+    }
+}
+
+// 解析查询参数并校验
+pub struct ValidQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AppQuery(mut value) = AppQuery::<T>::from_request_parts(parts, state).await?;
+        value.validate().map_err(AppError::validation)?;
+        Ok(ValidQuery(value))
+    }
+}
@@ -0,0 +1,1103 @@
+// HTTP API端到端测试，每个测试使用独立的内存数据库
+mod support;
+
+// 测试使用的存储后端；api_memory.rs 在内存存储后端上运行同一组测试
+#[allow(dead_code)]
+const TEST_DATABASE_URL: &str = "sqlite::memory:";
+
+use axum::http::{Method, StatusCode};
+use chrono::{NaiveDate, Utc};
+
+use demeter::{
+    bootstrap,
+    config::{BootstrapConfig, Config, DemoConfig, TrashConfig},
+    demo,
+    error::ErrorCode,
+    models::{CreateTodoRequest, SearchQuery, TodoListQuery, UpdateTodoRequest, UpdateUserSettingsRequest},
+    trash,
+};
+use support::{new_todo, password_for, TestApp, ALICE, BOB, READER};
+
+// ---- 登录和注册 ----
+
+#[tokio::test]
+async fn login_returns_token_and_user() {
+    let app = TestApp::spawn().await;
+    let response = app.client().login(ALICE, &password_for(ALICE)).await.unwrap();
+
+    assert_eq!(response.user.username, ALICE);
+    assert!(!response.user.readonly);
+    assert!(!response.token.is_empty());
+
+    // token可以直接用于后续请求
+    let client = app.client().with_token(response.token);
+    assert!(client.list_todos().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn login_rejects_wrong_password_and_unknown_user() {
+    let app = TestApp::spawn().await;
+    let client = app.client();
+
+    let failure = client.login(ALICE, "wrong-password").await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(failure.code(), Some(ErrorCode::InvalidCredentials));
+
+    // 不存在的用户与密码错误返回相同的错误，避免泄露用户名是否存在
+    let failure = client.login("nobody", "whatever-password").await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn register_then_login() {
+    let app = TestApp::spawn().await;
+    let client = app.client();
+
+    let response = client.register("  carol  ", "carol-password").await.unwrap();
+    assert!(response.success);
+    let user = response.data.unwrap();
+    // 用户名首尾空白被去掉
+    assert_eq!(user.username, "carol");
+    assert!(!user.public_access);
+    assert!(!user.readonly);
+
+    let login = client.login("carol", "carol-password").await.unwrap();
+    assert_eq!(login.user.id, user.id);
+}
+
+#[tokio::test]
+async fn register_rejects_taken_username() {
+    let app = TestApp::spawn().await;
+    let failure = app.client().register(ALICE, "another-password").await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::CONFLICT);
+    assert_eq!(failure.code(), Some(ErrorCode::UsernameTaken));
+}
+
+#[tokio::test]
+async fn register_reports_every_invalid_field() {
+    let app = TestApp::spawn().await;
+    let failure = app.client().register("a!", "123").await.unwrap_err();
+
+    assert_eq!(failure.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(failure.code(), Some(ErrorCode::ValidationFailed));
+    let fields = failure.fields();
+    assert!(fields.contains(&("username", "TOO_SHORT")), "{:?}", fields);
+    assert!(fields.contains(&("username", "INVALID_CHARS")), "{:?}", fields);
+    assert!(fields.contains(&("password", "TOO_SHORT")), "{:?}", fields);
+}
+
+#[tokio::test]
+async fn invalid_token_is_unauthorized() {
+    let app = TestApp::spawn().await;
+    let client = app.client().with_token("not-a-token");
+
+    let failure = client.create_titled("买牛奶").await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(failure.code(), Some(ErrorCode::Unauthorized));
+
+    let failure = app.client().create_titled("买牛奶").await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::Unauthorized));
+}
+
+#[tokio::test]
+async fn error_messages_follow_accept_language() {
+    let app = TestApp::spawn().await;
+
+    let zh = app.client().login(ALICE, "wrong-password").await.unwrap_err();
+    let en = app.client().with_locale("en-US,en;q=0.9").login(ALICE, "wrong-password").await.unwrap_err();
+    assert_eq!(zh.code(), en.code());
+    assert_ne!(zh.body.message, en.body.message);
+    assert!(en.body.message.is_ascii(), "{}", en.body.message);
+}
+
+// ---- 首次设置 ----
+
+#[tokio::test]
+async fn fresh_database_has_no_default_user() {
+    let app = TestApp::spawn_empty().await;
+    let client = app.client();
+
+    assert!(client.setup_status().await.unwrap().setup_required);
+    let failure = client.login("guest", "password").await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn setup_creates_the_first_user_once() {
+    let app = TestApp::spawn_empty().await;
+    let client = app.client();
+
+    let response = client.setup(" admin ", "admin-password", true).await.unwrap();
+    assert_eq!(response.user.username, "admin");
+    assert!(response.user.public_access);
+    assert!(!response.user.readonly);
+
+    // 返回的token可以直接使用
+    let admin = app.client().with_token(response.token);
+    admin.create_titled("第一个任务").await.unwrap();
+
+    let status = client.setup_status().await.unwrap();
+    assert!(!status.setup_required);
+    assert!(status.demo.is_none());
+
+    let failure = client.setup("mallory", "mallory-password", false).await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::CONFLICT);
+    assert_eq!(failure.code(), Some(ErrorCode::SetupCompleted));
+    assert!(app.storage.users().find_by_username("mallory").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn setup_validates_the_account() {
+    let app = TestApp::spawn_empty().await;
+    let failure = app.client().setup("a b", "123", false).await.unwrap_err();
+
+    assert_eq!(failure.code(), Some(ErrorCode::ValidationFailed));
+    let fields = failure.fields();
+    assert!(fields.contains(&("username", "INVALID_CHARS")), "{:?}", fields);
+    assert!(fields.contains(&("password", "TOO_SHORT")), "{:?}", fields);
+    assert!(app.client().setup_status().await.unwrap().setup_required);
+}
+
+#[tokio::test]
+async fn register_requires_setup_first() {
+    let app = TestApp::spawn_empty().await;
+    let client = app.client();
+
+    // 不能绕过首次设置注册出第一个用户
+    let failure = client.register("mallory", "mallory-password").await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::CONFLICT);
+    assert_eq!(failure.code(), Some(ErrorCode::SetupRequired));
+    assert!(client.setup_status().await.unwrap().setup_required);
+
+    client.setup("admin", "admin-password", false).await.unwrap();
+    let response = client.register("newuser", "newuser-password").await.unwrap();
+    assert_eq!(response.data.unwrap().username, "newuser");
+}
+
+#[tokio::test]
+async fn setup_is_unavailable_once_users_exist() {
+    let app = TestApp::spawn().await;
+    assert!(!app.client().setup_status().await.unwrap().setup_required);
+    let failure = app.client().setup("admin", "admin-password", false).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::SetupCompleted));
+}
+
+#[tokio::test]
+async fn bootstrap_config_creates_the_first_user() {
+    let app = TestApp::spawn_empty().await;
+    let config = BootstrapConfig {
+        username: "admin".to_string(),
+        password: "admin-password".to_string(),
+        public_access: false,
+    };
+
+    bootstrap::run(app.storage.users(), &config).await.unwrap();
+    app.client().login("admin", "admin-password").await.unwrap();
+
+    // 已有用户时忽略配置
+    let other = BootstrapConfig {
+        username: "other".to_string(),
+        ..config
+    };
+    bootstrap::run(app.storage.users(), &other).await.unwrap();
+    assert_eq!(app.storage.users().list().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn bootstrap_config_is_validated() {
+    let app = TestApp::spawn_empty().await;
+    let config = BootstrapConfig {
+        username: "admin".to_string(),
+        password: String::new(),
+        public_access: false,
+    };
+
+    assert!(bootstrap::run(app.storage.users(), &config).await.is_err());
+    assert!(bootstrap::setup_required(app.storage.users()).await.unwrap());
+    // 未配置时什么也不做
+    bootstrap::run(app.storage.users(), &BootstrapConfig::default()).await.unwrap();
+    assert!(bootstrap::setup_required(app.storage.users()).await.unwrap());
+}
+
+#[tokio::test]
+async fn demo_mode_seeds_and_resets_the_demo_account() {
+    let config = DemoConfig {
+        enabled: true,
+        ..DemoConfig::default()
+    };
+    let app = TestApp::spawn_with(Config {
+        demo: config.clone(),
+        ..Config::default()
+    })
+    .await;
+    demo::reset(app.storage.as_ref(), &config).await.unwrap();
+
+    let status = app.client().setup_status().await.unwrap();
+    let account = status.demo.unwrap();
+    assert_eq!((account.username.as_str(), account.password.as_str()), ("guest", "password"));
+
+    // 演示账户公开访问，示例任务分布在最近几周
+    let anonymous = app.client();
+    let samples = anonymous.list_todos().await.unwrap();
+    assert!(!samples.is_empty());
+    assert!(anonymous.history().await.unwrap().len() > 1);
+
+    let guest = app.login_as_with("guest", "password").await;
+    guest.create_titled("访客的任务").await.unwrap();
+    guest.delete_todo(samples[0].id).await.unwrap();
+    guest
+        .update_settings(&UpdateUserSettingsRequest {
+            public_access: Some(false),
+            locale: Some("en".to_string()),
+        })
+        .await
+        .unwrap();
+
+    demo::reset(app.storage.as_ref(), &config).await.unwrap();
+
+    // 重置后恢复示例数据和设置，已登录的访客不需要重新登录
+    let titles = |todos: Vec<demeter::models::TodoResponse>| todos.into_iter().map(|todo| todo.title).collect::<Vec<_>>();
+    assert_eq!(titles(guest.list_todos().await.unwrap()), titles(samples));
+    assert_eq!(anonymous.list_todos().await.unwrap().len(), guest.list_todos().await.unwrap().len());
+    assert!(guest.trash().await.unwrap().data.is_empty());
+    let user = app.storage.users().find_by_username("guest").await.unwrap().unwrap();
+    assert!(user.public_access);
+    assert!(user.demo);
+    assert_eq!(user.locale, None);
+}
+
+#[tokio::test]
+async fn demo_mode_does_not_take_over_a_real_user() {
+    let config = DemoConfig {
+        enabled: true,
+        username: ALICE.to_string(),
+        ..DemoConfig::default()
+    };
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let todo = alice.create_titled("真实用户的任务").await.unwrap();
+
+    let error = demo::reset(app.storage.as_ref(), &config).await.unwrap_err();
+    assert!(error.to_string().contains(ALICE), "{}", error);
+
+    // 密码、设置和任务都保持不变
+    let todos = alice.list_todos().await.unwrap();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(todos[0].id, todo.id);
+    app.client().login(ALICE, &password_for(ALICE)).await.unwrap();
+    let user = app.storage.users().find_by_username(ALICE).await.unwrap().unwrap();
+    assert!(!user.public_access);
+    assert!(!user.demo);
+}
+
+// ---- 任务增删改查 ----
+
+#[tokio::test]
+async fn todo_crud() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let created = alice
+        .create_todo(&CreateTodoRequest {
+            title: " 写周报 ".to_string(),
+            description: Some("   ".to_string()),
+            emoji: "📊".to_string(),
+            due_date: Some("2030-01-31".to_string()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.title, "写周报");
+    // 只有空白的描述视为未填写
+    assert_eq!(created.description, None);
+    assert_eq!(created.emoji, "📊");
+    assert!(!created.completed);
+    assert_eq!(created.due_date, NaiveDate::from_ymd_opt(2030, 1, 31));
+
+    let todos = alice.list_todos().await.unwrap();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(todos[0].id, created.id);
+
+    // 只修改提供的字段
+    let updated = alice
+        .update_todo(created.id, &UpdateTodoRequest {
+            completed: Some(true),
+            description: Some("本周完成的工作".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(updated.completed);
+    assert_eq!(updated.title, "写周报");
+    assert_eq!(updated.description.as_deref(), Some("本周完成的工作"));
+    assert_eq!(updated.due_date, created.due_date);
+    assert!(updated.updated_at >= created.updated_at);
+
+    // 空字符串清除截止日期和描述
+    let cleared = alice
+        .update_todo(created.id, &UpdateTodoRequest {
+            description: Some(String::new()),
+            due_date: Some(String::new()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(cleared.description, None);
+    assert_eq!(cleared.due_date, None);
+
+    alice.delete_todo(created.id).await.unwrap();
+    assert!(alice.list_todos().await.unwrap().is_empty());
+
+    let failure = alice.delete_todo(created.id).await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::NOT_FOUND);
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+}
+
+#[tokio::test]
+async fn todos_are_listed_newest_first() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    for title in ["第一", "第二", "第三"] {
+        alice.create_titled(title).await.unwrap();
+    }
+    let titles: Vec<String> = alice.list_todos().await.unwrap().into_iter().map(|todo| todo.title).collect();
+    assert_eq!(titles, ["第三", "第二", "第一"]);
+}
+
+#[tokio::test]
+async fn todo_list_is_paginated_with_cursors() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    for index in 1..=7 {
+        alice.create_titled(&format!("任务{}", index)).await.unwrap();
+    }
+
+    let mut query = TodoListQuery {
+        limit: Some(3),
+        ..Default::default()
+    };
+    let mut pages = Vec::new();
+    loop {
+        let page = alice.todo_page(&query).await.unwrap();
+        assert_eq!(page.meta.total, 7);
+        assert_eq!(page.meta.limit, 3);
+        pages.push(page.data.into_iter().map(|todo| todo.title).collect::<Vec<_>>());
+        match page.meta.next_cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(pages, [
+        vec!["任务7", "任务6", "任务5"],
+        vec!["任务4", "任务3", "任务2"],
+        vec!["任务1"],
+    ]);
+
+    // 刚好取完时没有下一页
+    let page = alice
+        .todo_page(&TodoListQuery {
+            limit: Some(7),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.data.len(), 7);
+    assert_eq!(page.meta.next_cursor, None);
+}
+
+#[tokio::test]
+async fn cursor_stays_stable_when_todos_are_added() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    for title in ["一", "二", "三", "四"] {
+        alice.create_titled(title).await.unwrap();
+    }
+
+    let first = alice
+        .todo_page(&TodoListQuery {
+            limit: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    // 翻页之间新建的任务不会让下一页重复或遗漏
+    alice.create_titled("五").await.unwrap();
+    let second = alice
+        .todo_page(&TodoListQuery {
+            limit: Some(2),
+            cursor: first.meta.next_cursor,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let titles: Vec<String> = second.data.into_iter().map(|todo| todo.title).collect();
+    assert_eq!(titles, ["二", "一"]);
+    assert_eq!(second.meta.total, 5);
+}
+
+#[tokio::test]
+async fn todo_list_filters() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let milk = alice
+        .create_todo(&CreateTodoRequest {
+            emoji: "🥛".to_string(),
+            description: Some("Whole MILK, 两盒".to_string()),
+            ..new_todo("买牛奶")
+        })
+        .await
+        .unwrap();
+    let report = alice.create_titled("写周报").await.unwrap();
+    let odd = alice.create_titled("100% done_ish").await.unwrap();
+    let cafe = alice.create_titled("ÉCLAIR au CAFÉ").await.unwrap();
+    alice
+        .update_todo(report.id, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let ids = |query: TodoListQuery| {
+        let alice = alice.clone();
+        async move {
+            let page = alice.todo_page(&query).await.unwrap();
+            assert_eq!(page.meta.total, page.data.len() as i64);
+            page.data.into_iter().map(|todo| todo.id).collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(ids(TodoListQuery { completed: Some(true), ..Default::default() }).await, [report.id]);
+    assert_eq!(ids(TodoListQuery { completed: Some(false), ..Default::default() }).await, [cafe.id, odd.id, milk.id]);
+    assert_eq!(ids(TodoListQuery { emoji: Some(" 🥛 ".to_string()), ..Default::default() }).await, [milk.id]);
+
+    // 搜索标题和描述，不区分大小写，非ASCII字母也一样
+    assert_eq!(ids(TodoListQuery { q: Some("milk".to_string()), ..Default::default() }).await, [milk.id]);
+    assert_eq!(ids(TodoListQuery { q: Some("éclair".to_string()), ..Default::default() }).await, [cafe.id]);
+    assert_eq!(ids(TodoListQuery { q: Some("Café".to_string()), ..Default::default() }).await, [cafe.id]);
+    assert_eq!(ids(TodoListQuery { q: Some("周报".to_string()), ..Default::default() }).await, [report.id]);
+    // % 和 _ 按普通字符匹配
+    assert_eq!(ids(TodoListQuery { q: Some("0%".to_string()), ..Default::default() }).await, [odd.id]);
+    assert_eq!(ids(TodoListQuery { q: Some("e_i".to_string()), ..Default::default() }).await, [odd.id]);
+    assert!(ids(TodoListQuery { q: Some("_".to_string()), completed: Some(true), ..Default::default() }).await.is_empty());
+    // 只有空白的关键词视为未设置
+    assert_eq!(ids(TodoListQuery { q: Some("  ".to_string()), ..Default::default() }).await.len(), 4);
+
+    // 按时间增量同步
+    let updated = app.storage.todos().find_by_id(report.id, app.user_id(ALICE).await).await.unwrap().unwrap();
+    let since = updated.updated_at.to_rfc3339();
+    assert_eq!(ids(TodoListQuery { updated_since: Some(since), ..Default::default() }).await, [report.id]);
+    let after = milk.created_at.to_rfc3339();
+    assert_eq!(ids(TodoListQuery { created_after: Some(after), ..Default::default() }).await, [cafe.id, odd.id, report.id]);
+    assert!(ids(TodoListQuery { created_after: Some("2999-01-01".to_string()), ..Default::default() }).await.is_empty());
+}
+
+#[tokio::test]
+async fn todo_list_validates_query() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let failure = alice
+        .todo_page(&TodoListQuery {
+            limit: Some(0),
+            cursor: Some("not-a-cursor".to_string()),
+            created_after: Some("yesterday".to_string()),
+            updated_since: Some("2024-13-01".to_string()),
+            emoji: Some("abc".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields = failure.fields();
+    assert!(fields.contains(&("emoji", "INVALID_EMOJI")), "{:?}", fields);
+    assert!(fields.contains(&("limit", "OUT_OF_RANGE")), "{:?}", fields);
+    assert!(fields.contains(&("cursor", "INVALID_CURSOR")), "{:?}", fields);
+    assert!(fields.contains(&("created_after", "INVALID_DATETIME")), "{:?}", fields);
+    assert!(fields.contains(&("updated_since", "INVALID_DATETIME")), "{:?}", fields);
+
+    let (status, _) = alice.send(Method::GET, "/api/todos?completed=maybe", None::<&()>).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 表单提交的空参数视为未设置
+    alice.create_titled("任务").await.unwrap();
+    for path in [
+        "/api/todos?q=",
+        "/api/todos?q=&completed=&limit=&emoji=&cursor=&created_after=&updated_since=",
+        "/api/todos?q=%20&completed=%20&limit=%20",
+    ] {
+        let (status, body) = alice.send(Method::GET, path, None::<&()>).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", path, String::from_utf8_lossy(&body));
+        let page: demeter::models::Page<demeter::models::TodoResponse> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.data.len(), 1, "{}", path);
+        assert_eq!(page.meta.limit, 50);
+    }
+    let (status, _) = alice.send(Method::GET, "/api/search?q=%E4%BB%BB%E5%8A%A1&limit=", None::<&()>).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn create_todo_validates_fields() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let failure = alice
+        .create_todo(&CreateTodoRequest {
+            title: "   ".to_string(),
+            description: None,
+            emoji: "ab".to_string(),
+            due_date: Some("2030-02-30".to_string()),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields = failure.fields();
+    assert!(fields.contains(&("title", "REQUIRED")), "{:?}", fields);
+    assert!(fields.contains(&("emoji", "INVALID_EMOJI")), "{:?}", fields);
+    assert!(fields.contains(&("due_date", "INVALID_DATE")), "{:?}", fields);
+
+    // 组合emoji算作单个字符
+    let todo = alice
+        .create_todo(&CreateTodoRequest {
+            emoji: "👨‍👩‍👧".to_string(),
+            ..new_todo("家庭聚会")
+        })
+        .await
+        .unwrap();
+    assert_eq!(todo.emoji, "👨‍👩‍👧");
+}
+
+#[tokio::test]
+async fn malformed_requests_use_the_error_format() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    // 缺少必需字段的JSON
+    let (status, body) = alice.send(Method::POST, "/api/todos", Some(&serde_json::json!({ "title": "x" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "INVALID_REQUEST");
+
+    // 非数字的任务ID
+    let (status, _) = alice.send(Method::DELETE, "/api/todos/abc", None::<&()>).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = alice.send(Method::GET, "/api/does-not-exist", None::<&()>).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn users_cannot_touch_each_others_todos() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+
+    let todo = alice.create_titled("alice的任务").await.unwrap();
+    assert!(bob.list_todos().await.unwrap().is_empty());
+
+    // 其他用户的任务视为不存在
+    let failure = bob
+        .update_todo(todo.id, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+    let failure = bob.delete_todo(todo.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+
+    let todos = alice.list_todos().await.unwrap();
+    assert_eq!(todos.len(), 1);
+    assert!(!todos[0].completed);
+}
+
+// ---- 回收站 ----
+
+#[tokio::test]
+async fn deleted_todos_go_to_the_trash() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let kept = alice.create_titled("保留的任务").await.unwrap();
+    let deleted = alice.create_titled("误删的任务").await.unwrap();
+    alice.delete_todo(deleted.id).await.unwrap();
+
+    // 回收站中的任务不出现在列表、统计、搜索和历史中
+    let page = alice.todo_page(&TodoListQuery::default()).await.unwrap();
+    assert_eq!(page.data.iter().map(|todo| todo.id).collect::<Vec<_>>(), [kept.id]);
+    assert_eq!(page.meta.total, 1);
+    assert_eq!(alice.search_ids("的任务").await, [kept.id]);
+    let history = alice.history().await.unwrap();
+    assert_eq!(history.iter().map(|day| day.count).sum::<i64>(), 1);
+
+    let trash = alice.trash().await.unwrap();
+    assert_eq!(trash.retention_days, 30);
+    assert_eq!(trash.data.len(), 1);
+    assert_eq!(trash.data[0].id, deleted.id);
+    assert!(trash.data[0].deleted_at.is_some());
+
+    // 回收站中的任务不能修改，也不能重复删除
+    let failure = alice
+        .update_todo(deleted.id, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+    let failure = alice.delete_todo(deleted.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+
+    let restored = alice.restore_todo(deleted.id).await.unwrap();
+    assert_eq!(restored.title, deleted.title);
+    assert_eq!(restored.created_at, deleted.created_at);
+    assert_eq!(restored.deleted_at, None);
+    assert!(restored.updated_at >= deleted.updated_at);
+    assert_eq!(alice.list_todos().await.unwrap().len(), 2);
+    assert!(alice.trash().await.unwrap().data.is_empty());
+
+    // 不在回收站中的任务不能恢复
+    let failure = alice.restore_todo(kept.id).await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::NOT_FOUND);
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+}
+
+#[tokio::test]
+async fn trash_is_private() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+    let reader = app.login_as(READER).await;
+
+    let todo = alice.create_titled("私人任务").await.unwrap();
+    alice.delete_todo(todo.id).await.unwrap();
+    alice
+        .update_settings(&UpdateUserSettingsRequest {
+            public_access: Some(true),
+            locale: None,
+        })
+        .await
+        .unwrap();
+
+    // 公开访问不包括回收站
+    let failure = app.client().trash().await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNAUTHORIZED);
+    assert!(bob.trash().await.unwrap().data.is_empty());
+    let failure = bob.restore_todo(todo.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+    let failure = reader.restore_todo(todo.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::ReadonlyUser));
+
+    assert_eq!(alice.trash().await.unwrap().data.len(), 1);
+}
+
+#[tokio::test]
+async fn expired_trash_is_purged() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+
+    let old = alice.create_titled("很久以前删除的任务").await.unwrap();
+    alice.delete_todo(old.id).await.unwrap();
+    let kept = bob.create_titled("没有删除的任务").await.unwrap();
+    let config = TrashConfig {
+        retention_days: 30,
+        ..Default::default()
+    };
+
+    // 未超过保留期限的任务保留
+    let now = Utc::now();
+    assert_eq!(trash::purge_expired(app.storage.as_ref(), &config, now).await.unwrap(), 0);
+    assert_eq!(alice.trash().await.unwrap().data.len(), 1);
+
+    // 保留天数为0时永久保留
+    let later = now + chrono::Duration::days(31);
+    let forever = TrashConfig {
+        retention_days: 0,
+        ..Default::default()
+    };
+    assert_eq!(trash::purge_expired(app.storage.as_ref(), &forever, later).await.unwrap(), 0);
+
+    // 超出时间范围的保留天数返回错误而不是panic
+    let too_long = TrashConfig {
+        retention_days: u32::MAX,
+        ..Default::default()
+    };
+    assert!(trash::purge_expired(app.storage.as_ref(), &too_long, later).await.is_err());
+
+    assert_eq!(trash::purge_expired(app.storage.as_ref(), &config, later).await.unwrap(), 1);
+    assert!(alice.trash().await.unwrap().data.is_empty());
+    let failure = alice.restore_todo(old.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+    assert_eq!(bob.list_todos().await.unwrap()[0].id, kept.id);
+}
+
+// ---- 只读用户 ----
+
+#[tokio::test]
+async fn readonly_user_cannot_write() {
+    let app = TestApp::spawn().await;
+    let reader = app.login_as(READER).await;
+    assert!(reader.list_todos().await.unwrap().is_empty());
+
+    let failure = reader.create_titled("不允许").await.unwrap_err();
+    assert_eq!(failure.status, StatusCode::FORBIDDEN);
+    assert_eq!(failure.code(), Some(ErrorCode::ReadonlyUser));
+
+    // 只读检查先于任务是否存在的检查
+    let failure = reader
+        .update_todo(1, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::ReadonlyUser));
+    let failure = reader.delete_todo(1).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::ReadonlyUser));
+}
+
+#[tokio::test]
+async fn readonly_flag_applies_without_logging_in_again() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let todo = alice.create_titled("可写").await.unwrap();
+
+    let alice_id = app.user_id(ALICE).await;
+    app.storage.users().set_readonly(alice_id, true).await.unwrap();
+
+    let failure = alice.delete_todo(todo.id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::ReadonlyUser));
+    assert_eq!(alice.list_todos().await.unwrap().len(), 1);
+}
+
+// ---- 公开访问 ----
+
+#[tokio::test]
+async fn anonymous_visitors_see_nothing_without_a_public_user() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    alice.create_titled("私有任务").await.unwrap();
+
+    let anonymous = app.client();
+    let page = anonymous.todo_page(&TodoListQuery::default()).await.unwrap();
+    assert!(page.data.is_empty());
+    assert_eq!(page.meta.total, 0);
+    assert!(anonymous.history().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn public_user_is_visible_to_anonymous_visitors() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+    alice.create_titled("私有任务").await.unwrap();
+    bob.create_titled("公开任务").await.unwrap();
+
+    let response = bob
+        .update_settings(&UpdateUserSettingsRequest {
+            public_access: Some(true),
+            locale: None,
+        })
+        .await
+        .unwrap();
+    assert!(response.data.unwrap().public_access);
+
+    let anonymous = app.client();
+    let todos = anonymous.list_todos().await.unwrap();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(todos[0].title, "公开任务");
+    assert_eq!(anonymous.history().await.unwrap().len(), 1);
+
+    // 匿名访客不能修改公开用户的任务
+    let failure = anonymous.delete_todo(todos[0].id).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::Unauthorized));
+
+    // 登录后只看到自己的任务
+    assert_eq!(alice.list_todos().await.unwrap()[0].title, "私有任务");
+
+    bob.update_settings(&UpdateUserSettingsRequest {
+        public_access: Some(false),
+        locale: None,
+    })
+    .await
+    .unwrap();
+    assert!(anonymous.list_todos().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn public_access_reports_the_first_user() {
+    let app = TestApp::spawn().await;
+    let first = app.storage.users().first().await.unwrap().unwrap();
+
+    let response = app.client().public_access().await.unwrap();
+    assert_eq!(response.username, first.username);
+    assert_eq!(response.public_access, first.public_access);
+
+    app.storage.users().set_public_access(first.id, !first.public_access).await.unwrap();
+    let response = app.client().public_access().await.unwrap();
+    assert_eq!(response.public_access, !first.public_access);
+}
+
+#[tokio::test]
+async fn user_locale_overrides_accept_language() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let response = alice
+        .update_settings(&UpdateUserSettingsRequest {
+            public_access: None,
+            locale: Some("en-GB".to_string()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.data.unwrap().locale.as_deref(), Some("en"));
+
+    // 语言偏好在认证后生效
+    let failure = alice.clone().with_locale("zh-CN").delete_todo(404).await.unwrap_err();
+    assert_eq!(failure.code(), Some(ErrorCode::TodoNotFound));
+    assert!(failure.body.message.is_ascii(), "{}", failure.body.message);
+
+    let failure = alice
+        .update_settings(&UpdateUserSettingsRequest {
+            public_access: None,
+            locale: Some("fr".to_string()),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.fields(), [("locale", "UNSUPPORTED_LOCALE")]);
+}
+
+// ---- 全文搜索 ----
+
+#[tokio::test]
+async fn search_ranks_and_highlights_matches() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let slides = alice
+        .create_todo(&CreateTodoRequest {
+            description: Some("Charts for the quarterly REPORT".to_string()),
+            ..new_todo("Prepare slides")
+        })
+        .await
+        .unwrap();
+    let report = alice.create_titled("Quarterly report").await.unwrap();
+    alice.create_titled("Groceries").await.unwrap();
+
+    // 标题中的匹配排在描述中的匹配之前；前缀也能匹配，不区分大小写
+    let query = SearchQuery {
+        q: "repo*".to_string(),
+        ..Default::default()
+    };
+    let page = alice.search(&query).await.unwrap();
+    assert_eq!(page.meta.total, 2);
+    assert_eq!(page.meta.next_cursor, None);
+    let ids: Vec<i64> = page.data.iter().map(|hit| hit.todo.id).collect();
+    assert_eq!(ids, [report.id, slides.id]);
+    assert!(page.data[0].score > page.data[1].score);
+    assert_eq!(page.data[0].title, "Quarterly <mark>repo</mark>rt");
+    assert_eq!(page.data[0].snippet, None);
+    assert_eq!(page.data[1].title, "Prepare slides");
+    assert_eq!(page.data[1].snippet.as_deref(), Some("Charts for the quarterly <mark>REPO</mark>RT"));
+
+    // 多个词需要同时匹配
+    assert_eq!(alice.search_ids("quarterly charts").await, [slides.id]);
+    assert!(alice.search_ids("quarterly groceries").await.is_empty());
+
+    // 高亮以外的文字做HTML转义
+    alice.create_titled("Fix <b>bold</b> & report").await.unwrap();
+    let query = SearchQuery {
+        q: "bold".to_string(),
+        ..Default::default()
+    };
+    let page = alice.search(&query).await.unwrap();
+    assert_eq!(page.data[0].title, "Fix &lt;b&gt;<mark>bold</mark>&lt;/b&gt; &amp; report");
+}
+
+#[tokio::test]
+async fn search_handles_chinese_text() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let weekly = alice
+        .create_todo(&CreateTodoRequest {
+            description: Some(format!("{}整理本周的工作进展，周五前发给组长", "背景说明。".repeat(20))),
+            ..new_todo("写周报")
+        })
+        .await
+        .unwrap();
+    let quarterly = alice.create_titled("准备季度汇报").await.unwrap();
+    let shopping = alice
+        .create_todo(&CreateTodoRequest {
+            description: Some("牛奶、鸡蛋、面包".to_string()),
+            ..new_todo("周末采购")
+        })
+        .await
+        .unwrap();
+
+    // 至少3个字符的词使用全文索引，更短的词逐条匹配
+    assert_eq!(alice.search_ids("季度汇报").await, [quarterly.id]);
+    assert_eq!(alice.search_ids("周报").await, [weekly.id]);
+    assert_eq!(alice.search_ids("牛奶").await, [shopping.id]);
+    assert_eq!(alice.search_ids("周末采购 牛奶").await, [shopping.id]);
+    assert_eq!(alice.search_ids("汇").await, [quarterly.id]);
+
+    // 长描述截取匹配附近的片段
+    let query = SearchQuery {
+        q: "工作进展".to_string(),
+        ..Default::default()
+    };
+    let page = alice.search(&query).await.unwrap();
+    assert_eq!(page.data[0].title, "写周报");
+    let snippet = page.data[0].snippet.as_deref().unwrap();
+    assert!(snippet.starts_with('…'), "{}", snippet);
+    assert!(snippet.contains("整理本周的<mark>工作进展</mark>，周五前发给组长"), "{}", snippet);
+    assert!(snippet.chars().count() < 100, "{}", snippet);
+}
+
+#[tokio::test]
+async fn search_folds_non_ascii_case() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let cafe = alice.create_titled("ÉCLAIR AU CAFÉ").await.unwrap();
+    let greek = alice
+        .create_todo(&CreateTodoRequest {
+            description: Some("ΣΟΦΙΑ".to_string()),
+            ..new_todo("Notes")
+        })
+        .await
+        .unwrap();
+
+    // 短词逐条匹配和全文索引对非ASCII字母的大小写处理一致
+    assert_eq!(alice.search_ids("éc").await, [cafe.id]);
+    assert_eq!(alice.search_ids("fé").await, [cafe.id]);
+    assert_eq!(alice.search_ids("éclair").await, [cafe.id]);
+    assert_eq!(alice.search_ids("café éc").await, [cafe.id]);
+    assert_eq!(alice.search_ids("σο").await, [greek.id]);
+    assert_eq!(alice.search_ids("σοφ").await, [greek.id]);
+
+    let query = SearchQuery {
+        q: "éc".to_string(),
+        ..Default::default()
+    };
+    let page = alice.search(&query).await.unwrap();
+    assert_eq!(page.data[0].title, "<mark>ÉC</mark>LAIR AU CAFÉ");
+}
+
+#[tokio::test]
+async fn search_index_follows_changes() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+
+    let todo = alice.create_titled("预约牙医").await.unwrap();
+    let removed = alice.create_titled("预约理发").await.unwrap();
+    bob.create_titled("预约体检").await.unwrap();
+    assert_eq!(alice.search_ids("预约").await, [removed.id, todo.id]);
+
+    alice
+        .update_todo(todo.id, &UpdateTodoRequest {
+            title: Some("复查牙齿".to_string()),
+            description: Some("上午十点的门诊".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    alice.delete_todo(removed.id).await.unwrap();
+
+    assert!(alice.search_ids("预约").await.is_empty());
+    assert_eq!(alice.search_ids("复查牙齿").await, [todo.id]);
+    assert_eq!(alice.search_ids("十点的门诊").await, [todo.id]);
+    // 只修改完成状态不影响搜索
+    alice
+        .update_todo(todo.id, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(alice.search_ids("复查牙齿").await, [todo.id]);
+}
+
+#[tokio::test]
+async fn search_validates_query() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+
+    let failure = alice
+        .search(&SearchQuery {
+            q: " \"*\" ".to_string(),
+            limit: Some(101),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(failure.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields = failure.fields();
+    assert!(fields.contains(&("q", "REQUIRED")), "{:?}", fields);
+    assert!(fields.contains(&("limit", "OUT_OF_RANGE")), "{:?}", fields);
+
+    let (status, _) = alice.send(Method::GET, "/api/search", None::<&()>).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+// ---- 历史 ----
+
+#[tokio::test]
+async fn history_groups_todos_by_day() {
+    let app = TestApp::spawn().await;
+    let alice = app.login_as(ALICE).await;
+    let bob = app.login_as(BOB).await;
+
+    let first = alice.create_titled("一").await.unwrap();
+    alice.create_titled("二").await.unwrap();
+    alice.create_titled("三").await.unwrap();
+    bob.create_titled("bob的任务").await.unwrap();
+    alice
+        .update_todo(first.id, &UpdateTodoRequest {
+            completed: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let history = alice.history().await.unwrap();
+    assert_eq!(history.len(), 1);
+    let today = &history[0];
+    assert_eq!(today.date, Utc::now().date_naive().to_string());
+    assert_eq!(today.count, 3);
+    assert_eq!(today.completed_count, 1);
+    // 当天的任务按创建时间升序
+    let titles: Vec<&str> = today.tasks.iter().map(|todo| todo.title.as_str()).collect();
+    assert_eq!(titles, ["一", "二", "三"]);
+
+    alice.delete_todo(first.id).await.unwrap();
+    let history = alice.history().await.unwrap();
+    assert_eq!((history[0].count, history[0].completed_count), (2, 0));
+
+    assert!(app.login_as(READER).await.history().await.unwrap().is_empty());
+}
+
+// ---- 探针 ----
+
+#[tokio::test]
+async fn probes_report_ready() {
+    let app = TestApp::spawn().await;
+    let client = app.client();
+
+    let (status, _) = client.send(Method::GET, "/healthz", None::<&()>).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = client.send(Method::GET, "/readyz", None::<&()>).await;
+    assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+
+    let (status, body) = client.send(Method::GET, "/api/version", None::<&()>).await;
+    assert_eq!(status, StatusCode::OK);
+    let version: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(version["schema_version"], version["latest_schema_version"]);
+}
@@ -0,0 +1,5 @@
+// 在内存存储后端上运行 api.rs 中的全部测试，保证各存储后端的行为一致
+#[path = "api.rs"]
+mod api;
+
+const TEST_DATABASE_URL: &str = "memory:";
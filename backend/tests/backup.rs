@@ -0,0 +1,178 @@
+// SQLite在线备份测试：使用临时目录中的数据库文件
+use chrono::Utc;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use demeter::{
+    backup,
+    config::DatabaseConfig,
+    database, migrations,
+    repository::{NewTodo, NewUser},
+    storage::{SqliteStorage, Storage},
+};
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "demeter-backup-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// 单个连接：sqlx在INSERT ... RETURNING返回第一行后才在后台结束语句，多个连接时刚写入的数据可能暂时不可见
+async fn open_database(path: &Path) -> Arc<dyn Storage> {
+    database::init_database(&DatabaseConfig {
+        url: format!("sqlite:{}", path.display()),
+        create_if_missing: true,
+        max_connections: 1,
+        min_connections: 1,
+        ..DatabaseConfig::default()
+    })
+    .await
+    .unwrap()
+}
+
+// 写入足够多的数据，使备份需要分多步复制
+async fn seed(storage: &dyn Storage, count: usize) -> i64 {
+    let user = storage
+        .users()
+        .insert(NewUser {
+            username: "alice",
+            password_hash: "hash",
+            public_access: false,
+            readonly: false,
+            demo: false,
+        })
+        .await
+        .unwrap();
+    let description = "备份".repeat(2000);
+    for i in 0..count {
+        storage
+            .todos()
+            .insert(NewTodo {
+                user_id: user.id,
+                title: &format!("任务{}", i),
+                description: Some(&description),
+                emoji: "📝",
+                due_date: None,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+    }
+    user.id
+}
+
+#[tokio::test]
+async fn backup_copies_the_whole_database() {
+    let dir = TempDir::new();
+    let storage = open_database(&dir.join("demeter.db")).await;
+    let user_id = seed(storage.as_ref(), 300).await;
+
+    let destination = dir.join("backups/demeter-backup.db");
+    backup::backup_to(storage.as_ref(), &destination).await.unwrap();
+    storage.close().await;
+
+    let copy = SqliteStorage::open_existing(&destination).await.unwrap();
+    copy.integrity_check().await.unwrap();
+    assert_eq!(
+        migrations::current_version(&copy).await.unwrap(),
+        migrations::latest_version(&copy)
+    );
+    assert_eq!(copy.todos().list_for_user(user_id).await.unwrap().len(), 300);
+    copy.close().await;
+}
+
+#[tokio::test]
+async fn backup_never_overwrites_an_existing_file() {
+    let dir = TempDir::new();
+    let storage = open_database(&dir.join("demeter.db")).await;
+    seed(storage.as_ref(), 1).await;
+
+    let destination = dir.join("existing.db");
+    fs::write(&destination, "keep me").unwrap();
+    let error = backup::backup_to(storage.as_ref(), &destination).await.unwrap_err();
+    assert!(error.to_string().contains("已存在"), "{}", error);
+    assert_eq!(fs::read_to_string(&destination).unwrap(), "keep me");
+    storage.close().await;
+}
+
+#[tokio::test]
+async fn restore_replaces_the_database_with_a_backup() {
+    let dir = TempDir::new();
+    let path = dir.join("demeter.db");
+    let storage = open_database(&path).await;
+    let user_id = seed(storage.as_ref(), 3).await;
+    let backup_path = dir.join("demeter-backup.db");
+    backup::backup_to(storage.as_ref(), &backup_path).await.unwrap();
+    storage.todos().delete(1, user_id).await.unwrap();
+    storage.close().await;
+
+    let config = DatabaseConfig {
+        url: format!("sqlite:{}", path.display()),
+        ..DatabaseConfig::default()
+    };
+    assert_eq!(backup::restore(&config, &backup_path).await.unwrap(), path);
+
+    // 恢复后是备份时的数据，原数据库保留一份
+    let storage = open_database(&path).await;
+    assert_eq!(storage.todos().list_for_user(user_id).await.unwrap().len(), 3);
+    storage.close().await;
+    let kept = fs::read_dir(&dir.0)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().contains(".before-restore-"))
+        .count();
+    assert!(kept >= 1);
+    assert_no_staging_files(&dir);
+}
+
+#[tokio::test]
+async fn restore_rejects_invalid_backups() {
+    let dir = TempDir::new();
+    let path = dir.join("demeter.db");
+    let storage = open_database(&path).await;
+    seed(storage.as_ref(), 1).await;
+    storage.close().await;
+    let original = fs::read(&path).unwrap();
+
+    let config = DatabaseConfig {
+        url: format!("sqlite:{}", path.display()),
+        ..DatabaseConfig::default()
+    };
+    let not_a_database = dir.join("garbage.db");
+    fs::write(&not_a_database, "not a database").unwrap();
+    assert!(backup::restore(&config, &not_a_database).await.is_err());
+
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_no_staging_files(&dir);
+}
+
+fn assert_no_staging_files(dir: &TempDir) {
+    for entry in fs::read_dir(&dir.0).unwrap() {
+        let name = entry.unwrap().file_name();
+        assert!(!name.to_string_lossy().contains(".restore-tmp"), "{:?}", name);
+    }
+}
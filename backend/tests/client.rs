@@ -0,0 +1,46 @@
+// 客户端截止日期解析测试
+#![cfg(feature = "client")]
+
+use chrono::NaiveDate;
+
+use demeter::client::parse_due_date;
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn due_dates_are_relative_to_today() {
+    // 2024-01-10 是星期三
+    let today = date("2024-01-10");
+    for (input, expected) in [
+        ("2024-02-29", "2024-02-29"),
+        (" Today ", "2024-01-10"),
+        ("明天", "2024-01-11"),
+        ("yesterday", "2024-01-09"),
+        ("+3", "2024-01-13"),
+        ("+3d", "2024-01-13"),
+        ("+2w", "2024-01-24"),
+        ("+-1", "2024-01-09"),
+        ("fri", "2024-01-12"),
+        ("wednesday", "2024-01-17"),
+    ] {
+        assert_eq!(parse_due_date(input, today), Some(date(expected)), "{}", input);
+    }
+}
+
+#[test]
+fn invalid_due_dates_are_rejected() {
+    let today = date("2024-01-10");
+    for input in ["", "someday", "+", "+xw", "2024-13-01"] {
+        assert_eq!(parse_due_date(input, today), None, "{}", input);
+    }
+}
+
+#[test]
+fn huge_offsets_are_rejected_without_panicking() {
+    let today = date("2024-01-10");
+    for input in ["+1000000000000000", "+1000000000000000w", "+9223372036854775807", "+-9223372036854775808", "+1000000000"] {
+        assert_eq!(parse_due_date(input, today), None, "{}", input);
+    }
+}
@@ -0,0 +1,149 @@
+// CORS中间件测试：通过预检请求检查哪些来源被允许
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use tower::ServiceExt;
+
+use demeter::{config::CorsConfig, cors};
+
+fn config(origins: &[&str]) -> CorsConfig {
+    CorsConfig {
+        allowed_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+        ..CorsConfig::default()
+    }
+}
+
+fn router(config: &CorsConfig) -> Router {
+    Router::new()
+        .route("/api/todos", get(|| async { "ok" }))
+        .layer(cors::build_layer(config).unwrap())
+}
+
+// 预检请求返回的 Access-Control-Allow-Origin，来源不被允许时为None
+async fn preflight(router: &Router, origin: &str) -> Option<String> {
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/api/todos")
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn exact_origins_match_case_insensitively() {
+    let router = router(&config(&["https://App.Example.com/"]));
+
+    assert_eq!(
+        preflight(&router, "https://app.example.com").await.as_deref(),
+        Some("https://app.example.com")
+    );
+    assert!(preflight(&router, "https://APP.EXAMPLE.COM").await.is_some());
+    assert!(preflight(&router, "http://app.example.com").await.is_none());
+    assert!(preflight(&router, "https://app.example.com:8443").await.is_none());
+    assert!(preflight(&router, "https://other.example.com").await.is_none());
+}
+
+#[tokio::test]
+async fn wildcard_matches_only_real_subdomains() {
+    let router = router(&config(&["https://*.example.com"]));
+
+    for allowed in ["https://app.example.com", "https://a.b.example.com", "https://my-app.example.com"] {
+        assert!(preflight(&router, allowed).await.is_some(), "{}", allowed);
+    }
+    for rejected in [
+        // 通配符不匹配域名本身
+        "https://example.com",
+        // 后缀只能出现在末尾
+        "https://example.com.evil.com",
+        "https://app.example.com.evil.com",
+        // 必须在点之后
+        "https://evilexample.com",
+        // 协议和端口都要一致
+        "http://app.example.com",
+        "https://app.example.com:8443",
+        "https://evil.com#.example.com",
+        "https://user@evil.com?.example.com",
+    ] {
+        assert!(preflight(&router, rejected).await.is_none(), "{}", rejected);
+    }
+}
+
+#[tokio::test]
+async fn wildcard_with_port_requires_that_port() {
+    let router = router(&config(&["http://*.localhost:5173"]));
+
+    assert!(preflight(&router, "http://app.localhost:5173").await.is_some());
+    assert!(preflight(&router, "http://app.localhost").await.is_none());
+    assert!(preflight(&router, "http://app.localhost:5174").await.is_none());
+}
+
+#[test]
+fn invalid_patterns_are_rejected_at_startup() {
+    for pattern in [
+        "example.com",
+        "*.example.com",
+        "https://*example.com",
+        "https://app.*.com",
+        "https://*.*.example.com",
+        "https://example.com/app",
+    ] {
+        assert!(cors::build_layer(&config(&[pattern])).is_err(), "{}", pattern);
+    }
+}
+
+#[tokio::test]
+async fn credentials_cannot_be_combined_with_wildcards() {
+    let with_credentials = |origins: &[&str], headers: &[&str]| CorsConfig {
+        allow_credentials: true,
+        allowed_headers: headers.iter().map(|header| header.to_string()).collect(),
+        ..config(origins)
+    };
+
+    assert!(cors::build_layer(&with_credentials(&["*"], &["content-type"])).is_err());
+    assert!(cors::build_layer(&with_credentials(&["https://app.example.com"], &["*"])).is_err());
+
+    // 子域名通配符是逐个匹配后回显具体来源，可以携带凭据
+    let router = router(&with_credentials(&["https://*.example.com"], &["content-type"]));
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/api/todos")
+        .header(header::ORIGIN, "https://app.example.com")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+}
+
+#[tokio::test]
+async fn any_origin_is_allowed_with_a_star() {
+    let router = router(&config(&["*"]));
+    assert_eq!(preflight(&router, "https://anything.test").await.as_deref(), Some("*"));
+}
+
+#[tokio::test]
+async fn same_origin_mode_allows_no_cross_origin_requests() {
+    let router = router(&CorsConfig {
+        same_origin_only: true,
+        ..config(&["https://app.example.com", "*"])
+    });
+
+    assert!(preflight(&router, "https://app.example.com").await.is_none());
+    assert!(preflight(&router, "https://anything.test").await.is_none());
+
+    // 同源请求不带Origin，正常处理
+    let request = Request::builder().uri("/api/todos").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+}
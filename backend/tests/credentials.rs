@@ -0,0 +1,33 @@
+// 客户端登录信息文件测试；修改了进程的环境变量，因此单独作为一个测试程序
+#![cfg(all(feature = "client", unix))]
+
+use std::{fs, os::unix::fs::PermissionsExt};
+
+use demeter::client::{Credentials, CREDENTIALS_PATH_ENV};
+
+#[test]
+fn credentials_file_is_private() {
+    let dir = std::env::temp_dir().join(format!("demeter-credentials-test-{}", std::process::id()));
+    let path = dir.join("nested/cli.toml");
+    std::env::set_var(CREDENTIALS_PATH_ENV, &path);
+
+    let credentials = Credentials {
+        server: "http://127.0.0.1:9999".to_string(),
+        username: "alice".to_string(),
+        token: "secret-token".to_string(),
+    };
+    assert_eq!(credentials.save().unwrap(), path);
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+    // 已有的文件权限过宽时同样收紧，并覆盖旧内容
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    fs::write(&path, "x".repeat(1000)).unwrap();
+    credentials.save().unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    let loaded = Credentials::load().unwrap().unwrap();
+    assert_eq!(loaded.token, "secret-token");
+
+    assert!(Credentials::remove().unwrap());
+    assert!(Credentials::load().unwrap().is_none());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,95 @@
+// 前端静态文件测试：使用临时目录作为前端构建产物
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tower::ServiceExt;
+
+use demeter::{config::FrontendConfig, frontend};
+
+const INDEX: &str = "<!doctype html><div id=\"app\"></div>";
+
+struct Dist(PathBuf);
+
+impl Dist {
+    fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "demeter-frontend-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(path.join("assets")).unwrap();
+        fs::write(path.join("index.html"), INDEX).unwrap();
+        fs::write(path.join("assets/index-abc123.js"), "console.log('app')").unwrap();
+        fs::write(path.join("favicon.ico"), "icon").unwrap();
+        Self(path)
+    }
+
+    fn router(&self) -> Router {
+        frontend::router(&FrontendConfig {
+            enabled: true,
+            directory: self.0.display().to_string(),
+            embedded: false,
+        })
+        .unwrap()
+        .unwrap()
+    }
+}
+
+impl Drop for Dist {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+async fn get(router: &Router, path: &str) -> (StatusCode, Option<String>, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let cache_control = response
+        .headers()
+        .get(header::CACHE_CONTROL)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, cache_control, String::from_utf8_lossy(&body).into_owned())
+}
+
+#[tokio::test]
+async fn serves_files_and_falls_back_to_index() {
+    let dist = Dist::new();
+    let router = dist.router();
+
+    let (status, cache_control, body) = get(&router, "/assets/index-abc123.js").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cache_control.as_deref(), Some("public, max-age=31536000, immutable"));
+    assert_eq!(body, "console.log('app')");
+
+    let (status, _, body) = get(&router, "/favicon.ico").await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "icon"));
+
+    // 前端路由，包括最后一段带点的路径
+    for path in ["/", "/history", "/user/john.doe", "/tag/v1.2", "/todos/42/"] {
+        let (status, cache_control, body) = get(&router, path).await;
+        assert_eq!(status, StatusCode::OK, "{}", path);
+        assert_eq!(body, INDEX, "{}", path);
+        assert_eq!(cache_control.as_deref(), Some("no-cache"), "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn missing_assets_are_not_found() {
+    let dist = Dist::new();
+    let router = dist.router();
+
+    for path in ["/assets/index-old.js", "/assets/chunk", "/logo.PNG", "/robots.txt", "/app.js.map"] {
+        let (status, _, _) = get(&router, path).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", path);
+    }
+}
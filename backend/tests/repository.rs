@@ -0,0 +1,242 @@
+// 存储层测试：在内存SQLite和内存存储上运行，设置DEMETER_TEST_POSTGRES_URL时也在PostgreSQL上运行
+use chrono::{Duration, Utc};
+use sqlx::{Connection, Executor, PgConnection};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use demeter::{
+    config::DatabaseConfig,
+    database,
+    repository::{ImportedTodo, NewTodo, NewUser, TodoFilter},
+    storage::Storage,
+};
+
+const POSTGRES_URL_ENV: &str = "DEMETER_TEST_POSTGRES_URL";
+
+// 每个测试要运行的存储后端
+fn database_urls() -> Vec<String> {
+    let mut urls = vec!["sqlite::memory:".to_string(), "memory:".to_string()];
+    urls.extend(std::env::var(POSTGRES_URL_ENV).ok().filter(|url| !url.is_empty()));
+    urls
+}
+
+struct TestDatabase {
+    storage: Arc<dyn Storage>,
+    // PostgreSQL上为本测试新建的数据库：(服务器URL, 数据库名)
+    postgres: Option<(String, String)>,
+}
+
+impl TestDatabase {
+    // 内存数据库只在连接存活期间存在，使用单个连接
+    async fn open(url: &str) -> Self {
+        let (url, postgres) = if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            let name = create_postgres_database(url).await;
+            (postgres_database_url(url, &name), Some((url.to_string(), name)))
+        } else {
+            (url.to_string(), None)
+        };
+        let storage = database::init_database(&DatabaseConfig {
+            url,
+            max_connections: 1,
+            min_connections: 1,
+            ..DatabaseConfig::default()
+        })
+        .await
+        .unwrap();
+        Self { storage, postgres }
+    }
+
+    fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
+    }
+
+    async fn close(self) {
+        self.storage.close().await;
+        if let Some((server, name)) = self.postgres {
+            let mut connection = PgConnection::connect(&server).await.unwrap();
+            connection.execute(format!("DROP DATABASE {}", name).as_str()).await.unwrap();
+        }
+    }
+}
+
+async fn create_postgres_database(server: &str) -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let name = format!("demeter_test_{}_{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+    let mut connection = PgConnection::connect(server).await.unwrap();
+    connection.execute(format!("DROP DATABASE IF EXISTS {}", name).as_str()).await.unwrap();
+    connection.execute(format!("CREATE DATABASE {}", name).as_str()).await.unwrap();
+    name
+}
+
+// 把服务器URL中的数据库名替换为name
+fn postgres_database_url(server: &str, name: &str) -> String {
+    let server = server.split('?').next().unwrap_or(server);
+    let host_start = server.find("://").map_or(0, |index| index + 3);
+    let host_end = server[host_start..].find('/').map_or(server.len(), |index| host_start + index);
+    format!("{}/{}", &server[..host_end], name)
+}
+
+async fn create_user(storage: &dyn Storage, username: &str) -> i64 {
+    storage
+        .users()
+        .insert(NewUser {
+            username,
+            password_hash: "hash",
+            public_access: false,
+            readonly: false,
+            demo: false,
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+fn imported(user_id: i64, title: &str, completed: bool) -> ImportedTodo<'_> {
+    let created_at = Utc::now() - Duration::days(2);
+    ImportedTodo {
+        user_id,
+        title,
+        description: None,
+        emoji: "📝",
+        due_date: None,
+        completed,
+        created_at,
+        updated_at: created_at + Duration::days(1),
+    }
+}
+
+#[tokio::test]
+async fn import_keeps_completion_and_timestamps() {
+    for url in database_urls() {
+        let database = TestDatabase::open(&url).await;
+        let storage = database.storage();
+        let user_id = create_user(storage, "alice").await;
+
+        let todos = [imported(user_id, "已完成", true), imported(user_id, "未完成", false)];
+        assert_eq!(storage.todos().import(&todos).await.unwrap(), 2, "{}", url);
+
+        let mut stored = storage.todos().list_for_user(user_id).await.unwrap();
+        stored.sort_by_key(|todo| todo.id);
+        assert_eq!(stored.len(), 2, "{}", url);
+        for (todo, expected) in stored.iter().zip(&todos) {
+            assert_eq!(todo.title, expected.title, "{}", url);
+            assert_eq!(todo.completed, expected.completed, "{}", url);
+            assert_eq!(todo.created_at.timestamp(), expected.created_at.timestamp(), "{}", url);
+            assert_eq!(todo.updated_at.timestamp(), expected.updated_at.timestamp(), "{}", url);
+        }
+        database.close().await;
+    }
+}
+
+#[tokio::test]
+async fn import_writes_nothing_when_an_insert_fails() {
+    let database = TestDatabase::open("sqlite::memory:").await;
+    let storage = database.storage();
+    let user_id = create_user(storage, "alice").await;
+
+    // 第二个任务违反外键约束，第一个任务也不能留下
+    let todos = [imported(user_id, "第一个", false), imported(user_id + 100, "第二个", false)];
+    assert!(storage.todos().import(&todos).await.is_err());
+    assert!(storage.todos().list_for_user(user_id).await.unwrap().is_empty());
+    database.close().await;
+}
+
+#[tokio::test]
+async fn count_by_user_skips_trashed_todos() {
+    for url in database_urls() {
+        let database = TestDatabase::open(&url).await;
+        let storage = database.storage();
+        let alice = create_user(storage, "alice").await;
+        let bob = create_user(storage, "bob").await;
+        let carol = create_user(storage, "carol").await;
+
+        let todos = [
+            imported(alice, "一", false),
+            imported(alice, "二", true),
+            imported(alice, "三", false),
+            imported(bob, "四", false),
+        ];
+        storage.todos().import(&todos).await.unwrap();
+        let trashed = storage.todos().list_for_user(alice).await.unwrap()[0].id;
+        assert!(storage.todos().trash(trashed, alice, Utc::now()).await.unwrap());
+
+        let counts = storage.todos().count_by_user().await.unwrap();
+        assert_eq!(counts.get(&alice), Some(&2), "{}", url);
+        assert_eq!(counts.get(&bob), Some(&1), "{}", url);
+        assert_eq!(counts.get(&carol), None, "{}", url);
+        database.close().await;
+    }
+}
+
+#[tokio::test]
+async fn todo_filter_folds_non_ascii_case() {
+    for url in database_urls() {
+        let database = TestDatabase::open(&url).await;
+        let storage = database.storage();
+        let user_id = create_user(storage, "alice").await;
+        let created_at = Utc::now();
+        for (offset, (title, description)) in [
+            ("ÉCLAIR au chocolat", None),
+            ("Kaffee", Some("im CAFÉ trinken")),
+            ("eclair ohne Akzent", None),
+            ("Straße", None),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            storage
+                .todos()
+                .insert(NewTodo {
+                    user_id,
+                    title,
+                    description,
+                    emoji: "📝",
+                    due_date: None,
+                    created_at: created_at + Duration::seconds(offset as i64),
+                })
+                .await
+                .unwrap();
+        }
+
+        for (search, expected) in [
+            ("éclair", vec!["ÉCLAIR au chocolat"]),
+            ("Café", vec!["Kaffee"]),
+            ("ECLAIR", vec!["eclair ohne Akzent"]),
+            ("STRASSE", vec![]),
+            ("straße", vec!["Straße"]),
+        ] {
+            let filter = TodoFilter {
+                search: Some(search.to_string()),
+                ..TodoFilter::default()
+            };
+            let titles: Vec<String> = storage
+                .todos()
+                .list_page(user_id, &filter, None, 10)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|todo| todo.title)
+                .collect();
+            assert_eq!(titles, expected, "{} q={}", url, search);
+            assert_eq!(
+                storage.todos().count(user_id, &filter).await.unwrap(),
+                expected.len() as i64,
+                "{} q={}",
+                url,
+                search
+            );
+        }
+
+        // 内存中筛选后再按limit截取
+        let filter = TodoFilter {
+            search: Some("É".to_string()),
+            ..TodoFilter::default()
+        };
+        let page = storage.todos().list_page(user_id, &filter, None, 2).await.unwrap();
+        let titles: Vec<&str> = page.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, ["Kaffee", "ÉCLAIR au chocolat"], "{}", url);
+        database.close().await;
+    }
+}
@@ -0,0 +1,314 @@
+// 端到端测试支持：在内存SQLite上启动完整的API路由，并提供带类型的请求客户端
+#![allow(dead_code)]
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use demeter::{
+    config::{Config, DatabaseConfig},
+    database,
+    error::ErrorCode,
+    models::{
+        ApiResponse, CreateTodoRequest, HistoryDay, LoginRequest, LoginResponse, Page, PublicAccessResponse,
+        RegisterRequest, SearchHit, SearchQuery, SetupRequest, SetupStatusResponse, TodoListQuery, TodoResponse,
+        TrashResponse, UpdateTodoRequest, UpdateUserSettingsRequest, UserResponse,
+    },
+    repository::NewUser,
+    storage::Storage,
+    validation::FieldError,
+    AppState,
+};
+
+// 预置用户，密码与用户名相同加上 -password 后缀
+pub const ALICE: &str = "alice";
+pub const BOB: &str = "bob";
+pub const READER: &str = "reader";
+
+// 测试中不需要默认强度的bcrypt，降低成本加快测试
+const TEST_BCRYPT_COST: u32 = 4;
+
+pub fn password_for(username: &str) -> String {
+    format!("{}-password", username)
+}
+
+pub struct TestApp {
+    router: Router,
+    pub storage: Arc<dyn Storage>,
+}
+
+impl TestApp {
+    // 每个测试使用独立的内存数据库，已完成迁移并预置 alice、bob（可写）和 reader（只读）
+    pub async fn spawn() -> Self {
+        let app = Self::spawn_empty().await;
+        for (username, readonly) in [(ALICE, false), (BOB, false), (READER, true)] {
+            let password_hash = bcrypt::hash(password_for(username), TEST_BCRYPT_COST).unwrap();
+            app.storage
+                .users()
+                .insert(NewUser {
+                    username,
+                    password_hash: &password_hash,
+                    public_access: false,
+                    readonly,
+                    demo: false,
+                })
+                .await
+                .expect("创建测试用户失败");
+        }
+        app
+    }
+
+    // 已完成迁移但还没有任何用户（首次设置之前）
+    pub async fn spawn_empty() -> Self {
+        Self::spawn_with(Config::default()).await
+    }
+
+    // 使用指定配置，数据库总是替换为测试入口的 TEST_DATABASE_URL（内存SQLite或内存存储）
+    pub async fn spawn_with(config: Config) -> Self {
+        // 内存数据库只在连接存活期间存在，所有请求共用同一个连接
+        let config = Config {
+            database: DatabaseConfig {
+                url: crate::TEST_DATABASE_URL.to_string(),
+                max_connections: 1,
+                min_connections: 1,
+                ..DatabaseConfig::default()
+            },
+            ..config
+        };
+
+        let storage = database::init_database(&config.database)
+            .await
+            .expect("初始化内存数据库失败");
+        let router = demeter::build_router(AppState::new(storage.clone(), &config));
+        Self { router, storage }
+    }
+
+    // 未登录的客户端
+    pub fn client(&self) -> TestClient {
+        TestClient {
+            router: self.router.clone(),
+            token: None,
+            locale: None,
+        }
+    }
+
+    // 以预置用户登录
+    pub async fn login_as(&self, username: &str) -> TestClient {
+        self.login_as_with(username, &password_for(username)).await
+    }
+
+    pub async fn login_as_with(&self, username: &str, password: &str) -> TestClient {
+        let mut client = self.client();
+        let response = client
+            .login(username, password)
+            .await
+            .unwrap_or_else(|failure| panic!("{} 登录失败: {:?}", username, failure));
+        client.token = Some(response.token);
+        client
+    }
+
+    pub async fn user_id(&self, username: &str) -> i64 {
+        self.storage.users().find_by_username(username).await.unwrap().unwrap().id
+    }
+}
+
+// 非2xx响应：状态码和统一格式的错误体
+#[derive(Debug)]
+pub struct Failure {
+    pub status: StatusCode,
+    pub body: ApiResponse<serde_json::Value>,
+}
+
+impl Failure {
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.body.code
+    }
+
+    pub fn field_errors(&self) -> &[FieldError] {
+        self.body.errors.as_deref().unwrap_or_default()
+    }
+
+    // 出错字段和错误码，便于断言
+    pub fn fields(&self) -> Vec<(&str, &str)> {
+        self.field_errors()
+            .iter()
+            .map(|error| (error.field.as_ref(), error.code.as_ref()))
+            .collect()
+    }
+}
+
+pub type TestResult<T> = Result<T, Failure>;
+
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+    token: Option<String>,
+    locale: Option<String>,
+}
+
+impl TestClient {
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    // 设置Accept-Language请求头
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    // 发送请求，返回状态码和原始响应体
+    pub async fn send(&self, method: Method, path: &str, body: Option<&impl Serialize>) -> (StatusCode, Vec<u8>) {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = &self.token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        if let Some(locale) = &self.locale {
+            request = request.header(header::ACCEPT_LANGUAGE, locale);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(body).unwrap())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, bytes.to_vec())
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> TestResult<T> {
+        let (status, bytes) = self.send(method, path, body).await;
+        if !status.is_success() {
+            let body = serde_json::from_slice(&bytes)
+                .unwrap_or_else(|e| panic!("错误响应格式不正确 ({}): {} {}", status, e, String::from_utf8_lossy(&bytes)));
+            return Err(Failure { status, body });
+        }
+        // 204等空响应体按null解析
+        let bytes = if bytes.is_empty() { b"null".to_vec() } else { bytes };
+        Ok(serde_json::from_slice(&bytes)
+            .unwrap_or_else(|e| panic!("响应格式不正确 ({}): {} {}", status, e, String::from_utf8_lossy(&bytes))))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> TestResult<T> {
+        self.request(Method::GET, path, None::<&()>).await
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> TestResult<LoginResponse> {
+        let body = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self.request(Method::POST, "/api/login", Some(&body)).await
+    }
+
+    pub async fn register(&self, username: &str, password: &str) -> TestResult<ApiResponse<UserResponse>> {
+        let body = RegisterRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self.request(Method::POST, "/api/register", Some(&body)).await
+    }
+
+    pub async fn setup_status(&self) -> TestResult<SetupStatusResponse> {
+        self.get("/api/setup").await
+    }
+
+    pub async fn setup(&self, username: &str, password: &str, public_access: bool) -> TestResult<LoginResponse> {
+        let body = SetupRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+            public_access,
+        };
+        self.request(Method::POST, "/api/setup", Some(&body)).await
+    }
+
+    // 第一页（默认每页数量）的任务
+    pub async fn list_todos(&self) -> TestResult<Vec<TodoResponse>> {
+        Ok(self.todo_page(&TodoListQuery::default()).await?.data)
+    }
+
+    pub async fn todo_page(&self, query: &TodoListQuery) -> TestResult<Page<TodoResponse>> {
+        let query = serde_urlencoded::to_string(query).unwrap();
+        self.get(&format!("/api/todos?{}", query)).await
+    }
+
+    pub async fn search(&self, query: &SearchQuery) -> TestResult<Page<SearchHit>> {
+        let query = serde_urlencoded::to_string(query).unwrap();
+        self.get(&format!("/api/search?{}", query)).await
+    }
+
+    // 搜索结果中任务的id，按相关度排序
+    pub async fn search_ids(&self, q: &str) -> Vec<i64> {
+        let query = SearchQuery {
+            q: q.to_string(),
+            ..Default::default()
+        };
+        let page = self.search(&query).await.unwrap();
+        page.data.into_iter().map(|hit| hit.todo.id).collect()
+    }
+
+    pub async fn create_todo(&self, todo: &CreateTodoRequest) -> TestResult<TodoResponse> {
+        self.request(Method::POST, "/api/todos", Some(todo)).await
+    }
+
+    // 只填标题，其余使用默认值
+    pub async fn create_titled(&self, title: &str) -> TestResult<TodoResponse> {
+        self.create_todo(&new_todo(title)).await
+    }
+
+    pub async fn update_todo(&self, id: i64, update: &UpdateTodoRequest) -> TestResult<TodoResponse> {
+        self.request(Method::PUT, &format!("/api/todos/{}", id), Some(update)).await
+    }
+
+    pub async fn delete_todo(&self, id: i64) -> TestResult<()> {
+        self.request(Method::DELETE, &format!("/api/todos/{}", id), None::<&()>).await
+    }
+
+    pub async fn restore_todo(&self, id: i64) -> TestResult<TodoResponse> {
+        self.request(Method::POST, &format!("/api/todos/{}/restore", id), None::<&()>).await
+    }
+
+    pub async fn trash(&self) -> TestResult<TrashResponse> {
+        self.get("/api/trash").await
+    }
+
+    pub async fn history(&self) -> TestResult<Vec<HistoryDay>> {
+        self.get("/api/history").await
+    }
+
+    pub async fn public_access(&self) -> TestResult<PublicAccessResponse> {
+        self.get("/api/public-access").await
+    }
+
+    pub async fn update_settings(&self, settings: &UpdateUserSettingsRequest) -> TestResult<ApiResponse<UserResponse>> {
+        self.request(Method::PUT, "/api/user/settings", Some(settings)).await
+    }
+}
+
+pub fn new_todo(title: &str) -> CreateTodoRequest {
+    CreateTodoRequest {
+        title: title.to_string(),
+        description: None,
+        emoji: "📝".to_string(),
+        due_date: None,
+    }
+}
@@ -0,0 +1,118 @@
+// Unix socket监听测试：socket文件权限以及遗留socket的处理
+#![cfg(unix)]
+
+use axum::{routing::get, Router};
+use std::{
+    fs,
+    io::{Read, Write},
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use demeter::{config::ServerConfig, server};
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "demeter-socket-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn config(path: &Path, mode: &str) -> ServerConfig {
+    ServerConfig {
+        unix_socket: path.display().to_string(),
+        unix_socket_mode: mode.to_string(),
+        ..ServerConfig::default()
+    }
+}
+
+fn app() -> Router {
+    Router::new().route("/", get(|| async { "ok" }))
+}
+
+// 等待服务开始监听
+async fn wait_for_socket(path: &Path) {
+    for _ in 0..100 {
+        if UnixStream::connect(path).is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{} 没有开始监听", path.display());
+}
+
+#[tokio::test]
+async fn socket_is_created_with_the_configured_mode() {
+    let dir = TempDir::new();
+    let path = dir.0.join("demeter.sock");
+    // 上次异常退出遗留的socket：文件存在但没有进程监听
+    drop(UnixListener::bind(&path).unwrap());
+
+    let server = tokio::spawn({
+        let config = config(&path, "600");
+        async move { server::run(&config, app()).await }
+    });
+    wait_for_socket(&path).await;
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+    let response = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || {
+            let mut stream = UnixStream::connect(path).unwrap();
+            stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        }
+    })
+    .await
+    .unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("ok"), "{}", response);
+
+    // 临时目录已清理，目录中只有socket
+    let entries: Vec<_> = fs::read_dir(&dir.0).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    assert_eq!(entries, ["demeter.sock"]);
+    server.abort();
+}
+
+#[tokio::test]
+async fn socket_of_a_running_instance_is_not_removed() {
+    let dir = TempDir::new();
+    let path = dir.0.join("demeter.sock");
+    let _running = UnixListener::bind(&path).unwrap();
+
+    let error = server::run(&config(&path, "660"), app()).await.unwrap_err();
+    assert!(error.to_string().contains("已有其他进程在监听"), "{}", error);
+    assert!(UnixStream::connect(&path).is_ok());
+}
+
+#[tokio::test]
+async fn regular_files_are_not_replaced() {
+    let dir = TempDir::new();
+    let path = dir.0.join("demeter.sock");
+    fs::write(&path, "keep me").unwrap();
+
+    let error = server::run(&config(&path, "660"), app()).await.unwrap_err();
+    assert!(error.to_string().contains("不是socket文件"), "{}", error);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+}
@@ -16,6 +16,11 @@ export interface PublicAccessResponse {
   username: string
 }
 
+export interface SetupStatusResponse {
+  setup_required: boolean
+  demo?: { username: string; password: string }
+}
+
 export const useAuthStore = defineStore('auth', () => {
   const user = ref<User | null>(null)
   const isAuthenticated = computed(() => !!user.value)
@@ -51,6 +56,40 @@ export const useAuthStore = defineStore('auth', () => {
     }
   }
 
+  // 首次设置：还没有任何用户时创建第一个用户并直接登录
+  const setup = async (username: string, password: string, publicAccess: boolean) => {
+    try {
+      const response = await axios.post(`${API_BASE}/setup`, {
+        username,
+        password,
+        public_access: publicAccess
+      })
+      user.value = response.data.user
+      token.value = response.data.token
+      localStorage.setItem('user', JSON.stringify(user.value))
+      localStorage.setItem('token', response.data.token)
+      return null
+    } catch (error) {
+      console.error('Setup failed:', error)
+      if (axios.isAxiosError(error) && error.response?.data?.message) {
+        const data = error.response.data
+        const details = (data.errors || []).map((e: { message: string }) => e.message).join('，')
+        return details ? `${data.message}：${details}` : data.message
+      }
+      return '首次设置失败'
+    }
+  }
+
+  const fetchSetupStatus = async (): Promise<SetupStatusResponse | null> => {
+    try {
+      const response = await axios.get(`${API_BASE}/setup`)
+      return response.data
+    } catch (error) {
+      console.error('Failed to fetch setup status:', error)
+      return null
+    }
+  }
+
   const logout = () => {
     user.value = null
     token.value = null
@@ -112,6 +151,8 @@ export const useAuthStore = defineStore('auth', () => {
     publicAccess, 
     token, 
     login, 
+    setup,
+    fetchSetupStatus,
     logout, 
     initAuth, 
     fetchPublicAccess, 
@@ -13,6 +13,16 @@ export interface Todo {
   completed: boolean
   created_at: string
   updated_at: string
+  deleted_at?: string
+}
+
+export interface Page<T> {
+  data: T[]
+  meta: {
+    total: number
+    limit: number
+    next_cursor: string | null
+  }
 }
 
 export interface HistoryDay {
@@ -31,8 +41,17 @@ export const useTodoStore = defineStore('todo', () => {
     try {
       const authStore = useAuthStore()
       const headers = authStore.getAuthHeaders()
-      const response = await axios.get(`${API_BASE}/todos`, { headers })
-      todos.value = response.data
+      // 接口按页返回，逐页取完全部任务
+      const all: Todo[] = []
+      let cursor: string | null = null
+      do {
+        const params: Record<string, string | number> = { limit: 200 }
+        if (cursor) params.cursor = cursor
+        const response = await axios.get<Page<Todo>>(`${API_BASE}/todos`, { headers, params })
+        all.push(...response.data.data)
+        cursor = response.data.meta.next_cursor
+      } while (cursor)
+      todos.value = all
     } catch (error) {
       console.error('Failed to fetch todos:', error)
     } finally {
@@ -75,8 +94,25 @@ export const useTodoStore = defineStore('todo', () => {
       const headers = authStore.getAuthHeaders()
       await axios.delete(`${API_BASE}/todos/${id}`, { headers })
       todos.value = todos.value.filter(t => t.id !== id)
+      return true
     } catch (error) {
       console.error('Failed to delete todo:', error)
+      return false
+    }
+  }
+
+  // 从回收站恢复，按创建时间放回列表中原来的位置
+  const restoreTodo = async (id: number) => {
+    try {
+      const authStore = useAuthStore()
+      const headers = authStore.getAuthHeaders()
+      const response = await axios.post(`${API_BASE}/todos/${id}/restore`, null, { headers })
+      todos.value.push(response.data)
+      todos.value.sort((a, b) => b.created_at.localeCompare(a.created_at))
+      return true
+    } catch (error) {
+      console.error('Failed to restore todo:', error)
+      return false
     }
   }
 
@@ -99,6 +135,7 @@ export const useTodoStore = defineStore('todo', () => {
     addTodo,
     toggleTodo,
     deleteTodo,
+    restoreTodo,
     getHistoryData
   }
 })